@@ -0,0 +1,171 @@
+use md5::{Digest, Md5};
+
+const DEFAULT_PORT: u16 = 11211;
+const POINTS_PER_SERVER: f32 = 160.0; // MEMCACHED_POINTS_PER_SERVER_KETAMA in libmemcached
+const POINTS_PER_HASH: u32 = 4;
+
+/// A weighted ketama continuum, compatible with libmemcached's `MEMCACHED_BEHAVIOR_KETAMA_WEIGHTED`
+/// distribution.
+///
+/// Each server is identified on the continuum by its hostname, plus its port when it is not the
+/// default port, so that keys are placed on the same servers as other libmemcached-based clients
+/// configured with the same server list.
+#[derive(Debug)]
+pub(crate) struct Continuum {
+    points: Vec<(u32, usize)>,
+}
+
+impl Continuum {
+    /// Builds the continuum from `(host, port, weight)` tuples, where each point maps to the index
+    /// of its server within `servers`.
+    pub(crate) fn new(servers: &[(String, u16, u32)]) -> Self {
+        let total_weight: u64 = servers.iter().map(|(_, _, weight)| *weight as u64).sum();
+        let server_count = servers.len() as f32;
+
+        let mut points = Vec::new();
+
+        for (index, (host, port, weight)) in servers.iter().enumerate() {
+            // libmemcached computes the number of points in single-precision floating point, which
+            // must be mirrored exactly to produce the same continuum for uneven weights.
+            let pct = *weight as f32 / total_weight as f32;
+            let points_per_server = ((pct * POINTS_PER_SERVER / POINTS_PER_HASH as f32
+                * server_count) as f64
+                + 0.0000000001)
+                .floor() as u32
+                * POINTS_PER_HASH;
+
+            for point_index in 0..points_per_server / POINTS_PER_HASH {
+                let sort_host = if *port == DEFAULT_PORT {
+                    format!("{}-{}", host, point_index)
+                } else {
+                    format!("{}:{}-{}", host, port, point_index)
+                };
+
+                let digest = Md5::digest(sort_host.as_bytes());
+                for chunk in digest.chunks_exact(4) {
+                    let value = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+                    points.push((value, index));
+                }
+            }
+        }
+
+        points.sort_unstable();
+
+        Continuum { points }
+    }
+
+    /// Returns the index of the server that owns the given key.
+    pub(crate) fn server_for(&self, key: &[u8]) -> usize {
+        let hash = hash_key(key);
+
+        // The key belongs to the first point at or after its hash, wrapping around the continuum.
+        let position = self.points.partition_point(|(value, _)| *value < hash);
+
+        match self.points.get(position) {
+            Some((_, index)) => *index,
+            None => self.points.first().map(|(_, index)| *index).unwrap_or(0),
+        }
+    }
+}
+
+fn hash_key(key: &[u8]) -> u32 {
+    let digest = Md5::digest(key);
+    u32::from_le_bytes([digest[0], digest[1], digest[2], digest[3]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Continuum;
+
+    static KEYS: [&str; 9] = [
+        "apple",
+        "banana",
+        "cherry",
+        "user:1234",
+        "session:abcdef",
+        "key-with-a-much-longer-name-than-the-others",
+        "0",
+        "1",
+        "2",
+    ];
+
+    fn servers(servers: &[(&str, u16, u32)]) -> Vec<(String, u16, u32)> {
+        servers
+            .iter()
+            .map(|(host, port, weight)| (host.to_string(), *port, *weight))
+            .collect()
+    }
+
+    fn points_per_server(continuum: &Continuum, server_count: usize) -> Vec<usize> {
+        (0..server_count)
+            .map(|i| continuum.points.iter().filter(|(_, s)| *s == i).count())
+            .collect()
+    }
+
+    // Expected values below were generated with an independent implementation of libmemcached's
+    // weighted ketama `update_continuum`.
+
+    #[test]
+    fn test_continuum_with_equal_weights() {
+        let continuum = Continuum::new(&servers(&[
+            ("10.0.1.1", 11211, 1),
+            ("10.0.1.2", 11212, 1),
+            ("cache-3.internal", 11211, 1),
+        ]));
+
+        assert_eq!(points_per_server(&continuum, 3), vec![160, 160, 160]);
+        assert_eq!(
+            &continuum.points[..3],
+            &[(1667432, 2), (13584380, 2), (17349376, 0)]
+        );
+
+        let expected = [0, 1, 2, 0, 0, 1, 2, 1, 0];
+        for (key, server) in KEYS.iter().zip(expected) {
+            assert_eq!(continuum.server_for(key.as_bytes()), server, "key {}", key);
+        }
+    }
+
+    #[test]
+    fn test_continuum_with_uneven_weights() {
+        let continuum = Continuum::new(&servers(&[
+            ("10.0.1.1", 11211, 1),
+            ("10.0.1.2", 11212, 1),
+            ("cache-3.internal", 11211, 2),
+        ]));
+
+        assert_eq!(points_per_server(&continuum, 3), vec![120, 120, 240]);
+        assert_eq!(
+            &continuum.points[..3],
+            &[(1667432, 2), (13584380, 2), (28391625, 2)]
+        );
+
+        let expected = [1, 1, 2, 0, 0, 2, 2, 0, 0];
+        for (key, server) in KEYS.iter().zip(expected) {
+            assert_eq!(continuum.server_for(key.as_bytes()), server, "key {}", key);
+        }
+    }
+
+    #[test]
+    fn test_continuum_with_ipv6_and_unix_socket_servers() {
+        let continuum = Continuum::new(&servers(&[
+            ("::1", 11211, 1),
+            ("/tmp/memcached.sock", 0, 3),
+        ]));
+
+        assert_eq!(points_per_server(&continuum, 2), vec![80, 240]);
+
+        let expected = [1, 1, 1, 1, 1, 1, 1, 1, 0];
+        for (key, server) in KEYS.iter().zip(expected) {
+            assert_eq!(continuum.server_for(key.as_bytes()), server, "key {}", key);
+        }
+    }
+
+    #[test]
+    fn test_single_server_owns_every_key() {
+        let continuum = Continuum::new(&servers(&[("127.0.0.1", 11211, 1)]));
+
+        for key in KEYS.iter() {
+            assert_eq!(continuum.server_for(key.as_bytes()), 0);
+        }
+    }
+}
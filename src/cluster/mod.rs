@@ -0,0 +1,843 @@
+use std::borrow::Cow;
+use std::io;
+
+use futures::future::join_all;
+use fxhash::FxHashMap;
+
+use crate::parser::MetaValue;
+use crate::{
+    AsMemcachedValue, AsciiProtocol, CasOutcome, Client, ClientConfig, Error, KeyPolicy,
+    MetaArithmeticFlags, MetaDebugInfo, MetaDeleteFlags, MetaGetFlags, MetaProtocol, MetaSetFlags,
+    MetaSetItem, MetaSetOutcome, Status, Value,
+};
+
+mod ketama;
+use self::ketama::Continuum;
+
+const DEFAULT_PORT: u16 = 11211;
+
+/// High-level client for a fleet of memcached servers.
+///
+/// [`Cluster`] holds one [`Client`] per server and distributes keys between them with a weighted
+/// ketama continuum that is compatible with libmemcached, so that keys are placed on the same
+/// servers as other libmemcached-based clients configured with the same server list and weights.
+///
+/// Single-key operations are routed to the server that owns the key, while multi-key operations
+/// are split up by server, executed concurrently, and merged back into a single result.
+pub struct Cluster {
+    clients: Vec<Client>,
+    continuum: Continuum,
+    key_policy: Option<KeyPolicy>,
+}
+
+impl Cluster {
+    /// Creates a new [`Cluster`] from the given data source strings, with every server weighted
+    /// equally.
+    ///
+    /// Each DSN follows the same format as [`Client::new`].
+    pub async fn new<I, S>(dsns: I) -> Result<Cluster, Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self::with_weights(dsns.into_iter().map(|dsn| (dsn, 1))).await
+    }
+
+    /// Creates a new [`Cluster`] from the given data source strings and their weights.
+    ///
+    /// Servers receive a share of the keyspace proportional to their weight.
+    pub async fn with_weights<I, S>(servers: I) -> Result<Cluster, Error>
+    where
+        I: IntoIterator<Item = (S, u32)>,
+        S: AsRef<str>,
+    {
+        Self::with_config(servers, ClientConfig::default()).await
+    }
+
+    /// Creates a new [`Cluster`] from the given data source strings and their weights, using the
+    /// given configuration for every server.
+    ///
+    /// If the configuration has a [`KeyPolicy`], keys are placed on servers by the key that is sent
+    /// to them, so hashed keys are placed where other clients using the same policy place them.
+    pub async fn with_config<I, S>(servers: I, config: ClientConfig) -> Result<Cluster, Error>
+    where
+        I: IntoIterator<Item = (S, u32)>,
+        S: AsRef<str>,
+    {
+        let key_policy = config.key_policy.clone();
+        let mut clients = Vec::new();
+        let mut identities = Vec::new();
+
+        for (dsn, weight) in servers {
+            let (host, port) = server_identity(dsn.as_ref())?;
+            identities.push((host, port, weight));
+            clients.push(Client::with_config(dsn, config.clone()).await?);
+        }
+
+        if clients.is_empty() || identities.iter().all(|(_, _, weight)| *weight == 0) {
+            return Err(Error::Connect(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cluster requires at least one server with a non-zero weight",
+            )));
+        }
+
+        Ok(Cluster {
+            clients,
+            continuum: Continuum::new(&identities),
+            key_policy,
+        })
+    }
+
+    /// Returns the [`Client`] for the server that owns the given key.
+    pub fn client_for_key<K: AsRef<[u8]>>(&mut self, key: K) -> &mut Client {
+        let index = self.server_for(key.as_ref());
+        &mut self.clients[index]
+    }
+
+    /// Returns the [`Client`]s for every server in the cluster, in the order they were provided.
+    pub fn clients_mut(&mut self) -> &mut [Client] {
+        &mut self.clients
+    }
+
+    // Picks the server for a key from the key that is sent to it, which the key policy may have
+    // hashed.  Keys that would be rejected anyway are placed by their original bytes.
+    fn server_for(&self, key: &[u8]) -> usize {
+        let key =
+            crate::prepare_key(key, false, self.key_policy.as_ref()).unwrap_or(Cow::Borrowed(key));
+        self.continuum.server_for(&key)
+    }
+
+    fn partition<T, F>(&self, items: impl IntoIterator<Item = T>, key: F) -> Vec<Vec<T>>
+    where
+        F: Fn(&T) -> &[u8],
+    {
+        let mut shards: Vec<Vec<T>> = (0..self.clients.len()).map(|_| Vec::new()).collect();
+
+        for item in items {
+            let index = self.server_for(key(&item));
+            shards[index].push(item);
+        }
+
+        shards
+    }
+
+    async fn retrieval_command_multi<I, K>(
+        &mut self,
+        command: &[u8],
+        keys: I,
+    ) -> Result<Vec<Value>, Error>
+    where
+        I: IntoIterator<Item = K>,
+        K: AsRef<[u8]>,
+    {
+        let shards = self.partition(keys, |key| key.as_ref());
+
+        let results = join_all(
+            self.clients
+                .iter_mut()
+                .zip(shards)
+                .filter(|(_, keys)| !keys.is_empty())
+                .map(|(client, keys)| client.retrieval_command_multi(command, keys)),
+        )
+        .await;
+
+        let mut values = Vec::new();
+        for result in results {
+            match result {
+                Ok(shard_values) => values.extend(shard_values),
+                // A shard with no hits reports NotFound, which only applies to the whole request
+                // if every shard missed.
+                Err(Error::Protocol(Status::NotFound)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        if values.is_empty() {
+            return Err(Status::NotFound.into());
+        }
+
+        Ok(values)
+    }
+
+    async fn storage_command_multi<'a, K, V>(
+        &mut self,
+        command: &[u8],
+        kv: &'a [(K, V)],
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> Result<FxHashMap<&'a K, Result<(), Error>>, Error>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash,
+        V: AsMemcachedValue,
+    {
+        let shards = self.partition(kv, |(key, _)| key.as_ref());
+
+        // Every shard is driven to completion even if another fails, as abandoning an operation
+        // partway through would poison that shard's client.
+        let shard_results = join_all(
+            self.clients
+                .iter_mut()
+                .zip(shards)
+                .filter(|(_, kv)| !kv.is_empty())
+                .map(|(client, kv)| async move {
+                    client
+                        .storage_command_multi(command, kv.iter().copied(), ttl, flags)
+                        .await
+                }),
+        )
+        .await;
+
+        let mut results = FxHashMap::with_capacity_and_hasher(kv.len(), Default::default());
+        for shard_result in shard_results {
+            results.extend(shard_result?);
+        }
+
+        Ok(results)
+    }
+}
+
+// Derives the host and port that identify a server on the continuum from its DSN, matching the
+// hostname and port libmemcached would be configured with for the same server.
+fn server_identity(dsn: &str) -> Result<(String, u16), Error> {
+    let invalid = || {
+        Error::Connect(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("failed to parse server identity from DSN: {}", dsn),
+        ))
+    };
+
+    match url::Url::parse(dsn) {
+        Ok(url) if url.scheme() == "unix" => Ok((url.path().to_string(), 0)),
+        Ok(url) if matches!(url.scheme(), "tcp" | "tls" | "tcp+tls") => {
+            let host = match url.host().ok_or_else(invalid)? {
+                url::Host::Ipv6(addr) => addr.to_string(),
+                host => host.to_string(),
+            };
+            Ok((host, url.port().unwrap_or(DEFAULT_PORT)))
+        }
+        // DSNs without a scheme, such as `localhost:11211`.
+        _ => match dsn.rsplit_once(':') {
+            Some((host, port)) => {
+                let host = host.trim_start_matches('[').trim_end_matches(']');
+                Ok((host.to_string(), port.parse().map_err(|_| invalid())?))
+            }
+            None => Ok((dsn.to_string(), DEFAULT_PORT)),
+        },
+    }
+}
+
+impl AsciiProtocol for Cluster {
+    async fn get<K: AsRef<[u8]>>(&mut self, key: K) -> Result<Option<Value>, Error> {
+        self.client_for_key(key.as_ref()).get(key).await
+    }
+
+    async fn get_multi<I, K>(&mut self, keys: I) -> Result<Vec<Value>, Error>
+    where
+        I: IntoIterator<Item = K>,
+        K: AsRef<[u8]>,
+    {
+        self.retrieval_command_multi(b"get", keys).await
+    }
+
+    async fn get_many<I, K>(&mut self, keys: I) -> Result<Vec<Value>, Error>
+    where
+        I: IntoIterator<Item = K>,
+        K: AsRef<[u8]>,
+    {
+        self.get_multi(keys).await
+    }
+
+    async fn gets<K: AsRef<[u8]>>(&mut self, key: K) -> Result<Option<Value>, Error> {
+        self.client_for_key(key.as_ref()).gets(key).await
+    }
+
+    async fn gets_multi<I, K>(&mut self, keys: I) -> Result<Vec<Value>, Error>
+    where
+        I: IntoIterator<Item = K>,
+        K: AsRef<[u8]>,
+    {
+        self.retrieval_command_multi(b"gets", keys).await
+    }
+
+    async fn gat<K: AsRef<[u8]>>(&mut self, key: K, ttl: i64) -> Result<Option<Value>, Error> {
+        self.client_for_key(key.as_ref()).gat(key, ttl).await
+    }
+
+    async fn gat_multi<I, K>(&mut self, keys: I, ttl: i64) -> Result<Vec<Value>, Error>
+    where
+        I: IntoIterator<Item = K>,
+        K: AsRef<[u8]>,
+    {
+        self.retrieval_command_multi(format!("gat {}", ttl).as_bytes(), keys)
+            .await
+    }
+
+    async fn gats<K: AsRef<[u8]>>(&mut self, key: K, ttl: i64) -> Result<Option<Value>, Error> {
+        self.client_for_key(key.as_ref()).gats(key, ttl).await
+    }
+
+    async fn gats_multi<I, K>(&mut self, keys: I, ttl: i64) -> Result<Vec<Value>, Error>
+    where
+        I: IntoIterator<Item = K>,
+        K: AsRef<[u8]>,
+    {
+        self.retrieval_command_multi(format!("gats {}", ttl).as_bytes(), keys)
+            .await
+    }
+
+    async fn touch<K>(&mut self, key: K, ttl: i64) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        self.client_for_key(key.as_ref()).touch(key, ttl).await
+    }
+
+    async fn set<K, V>(
+        &mut self,
+        key: K,
+        value: V,
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue,
+    {
+        self.client_for_key(key.as_ref())
+            .set(key, value, ttl, flags)
+            .await
+    }
+
+    async fn set_multi<'a, K, V>(
+        &mut self,
+        kv: &'a [(K, V)],
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> Result<FxHashMap<&'a K, Result<(), Error>>, Error>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash + std::fmt::Debug,
+        V: AsMemcachedValue,
+    {
+        self.storage_command_multi(b"set", kv, ttl, flags).await
+    }
+
+    async fn add<K, V>(
+        &mut self,
+        key: K,
+        value: V,
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue,
+    {
+        self.client_for_key(key.as_ref())
+            .add(key, value, ttl, flags)
+            .await
+    }
+
+    async fn add_multi<'a, K, V>(
+        &mut self,
+        kv: &'a [(K, V)],
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> Result<FxHashMap<&'a K, Result<(), Error>>, Error>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash + std::fmt::Debug,
+        V: AsMemcachedValue,
+    {
+        self.storage_command_multi(b"add", kv, ttl, flags).await
+    }
+
+    async fn replace<K, V>(
+        &mut self,
+        key: K,
+        value: V,
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue,
+    {
+        self.client_for_key(key.as_ref())
+            .replace(key, value, ttl, flags)
+            .await
+    }
+
+    async fn replace_multi<'a, K, V>(
+        &mut self,
+        kv: &'a [(K, V)],
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> Result<FxHashMap<&'a K, Result<(), Error>>, Error>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash + std::fmt::Debug,
+        V: AsMemcachedValue,
+    {
+        self.storage_command_multi(b"replace", kv, ttl, flags).await
+    }
+
+    async fn append<K, V>(&mut self, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue,
+    {
+        self.client_for_key(key.as_ref()).append(key, value).await
+    }
+
+    async fn append_multi<'a, K, V>(
+        &mut self,
+        kv: &'a [(K, V)],
+    ) -> Result<FxHashMap<&'a K, Result<(), Error>>, Error>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash + std::fmt::Debug,
+        V: AsMemcachedValue,
+    {
+        self.storage_command_multi(b"append", kv, None, None).await
+    }
+
+    async fn prepend<K, V>(&mut self, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue,
+    {
+        self.client_for_key(key.as_ref()).prepend(key, value).await
+    }
+
+    async fn prepend_multi<'a, K, V>(
+        &mut self,
+        kv: &'a [(K, V)],
+    ) -> Result<FxHashMap<&'a K, Result<(), Error>>, Error>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash + std::fmt::Debug,
+        V: AsMemcachedValue,
+    {
+        self.storage_command_multi(b"prepend", kv, None, None).await
+    }
+
+    async fn cas<K, V>(
+        &mut self,
+        key: K,
+        value: V,
+        cas_unique: u64,
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> Result<CasOutcome, Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue,
+    {
+        self.client_for_key(key.as_ref())
+            .cas(key, value, cas_unique, ttl, flags)
+            .await
+    }
+
+    async fn delete_multi_no_reply<K>(&mut self, keys: &[K]) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        let shards = self.partition(keys, |key| key.as_ref());
+
+        join_all(
+            self.clients
+                .iter_mut()
+                .zip(shards)
+                .filter(|(_, keys)| !keys.is_empty())
+                .map(|(client, keys)| async move { client.delete_multi_no_reply(&keys).await }),
+        )
+        .await
+        .into_iter()
+        .collect()
+    }
+
+    async fn delete_no_reply<K>(&mut self, key: K) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        self.client_for_key(key.as_ref()).delete_no_reply(key).await
+    }
+
+    async fn delete<K>(&mut self, key: K) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        self.client_for_key(key.as_ref()).delete(key).await
+    }
+
+    async fn increment<K>(&mut self, key: K, amount: u64) -> Result<u64, Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        self.client_for_key(key.as_ref())
+            .increment(key, amount)
+            .await
+    }
+
+    async fn increment_no_reply<K>(&mut self, key: K, amount: u64) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        self.client_for_key(key.as_ref())
+            .increment_no_reply(key, amount)
+            .await
+    }
+
+    async fn decrement<K>(&mut self, key: K, amount: u64) -> Result<u64, Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        self.client_for_key(key.as_ref())
+            .decrement(key, amount)
+            .await
+    }
+
+    async fn decrement_no_reply<K>(&mut self, key: K, amount: u64) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        self.client_for_key(key.as_ref())
+            .decrement_no_reply(key, amount)
+            .await
+    }
+}
+
+impl MetaProtocol for Cluster {
+    async fn meta_get<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        meta_flags: Option<&[&str]>,
+    ) -> Result<Option<MetaValue>, Error> {
+        self.client_for_key(key.as_ref())
+            .meta_get(key, is_quiet, opaque, meta_flags)
+            .await
+    }
+
+    async fn meta_get_with<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        flags: &MetaGetFlags,
+    ) -> Result<Option<MetaValue>, Error> {
+        self.client_for_key(key.as_ref())
+            .meta_get_with(key, is_quiet, opaque, flags)
+            .await
+    }
+
+    async fn meta_get_multi<'a, K>(
+        &mut self,
+        keys: &'a [K],
+        meta_flags: Option<&[&str]>,
+    ) -> Result<FxHashMap<&'a K, MetaValue>, Error>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash,
+    {
+        let shards = self.partition(keys, |key| key.as_ref());
+
+        let shard_results = join_all(
+            self.clients
+                .iter_mut()
+                .zip(shards)
+                .filter(|(_, keys)| !keys.is_empty())
+                .map(|(client, keys)| client.meta_get_multi_command(keys, meta_flags)),
+        )
+        .await;
+
+        let mut values = FxHashMap::with_capacity_and_hasher(keys.len(), Default::default());
+        for shard_result in shard_results {
+            values.extend(shard_result?);
+        }
+
+        Ok(values)
+    }
+
+    async fn meta_set<K, V>(
+        &mut self,
+        key: K,
+        value: V,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        meta_flags: Option<&[&str]>,
+    ) -> Result<Option<MetaValue>, Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue,
+    {
+        self.client_for_key(key.as_ref())
+            .meta_set(key, value, is_quiet, opaque, meta_flags)
+            .await
+    }
+
+    async fn meta_set_with<K, V>(
+        &mut self,
+        key: K,
+        value: V,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        flags: &MetaSetFlags,
+    ) -> Result<MetaSetOutcome, Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue,
+    {
+        self.client_for_key(key.as_ref())
+            .meta_set_with(key, value, is_quiet, opaque, flags)
+            .await
+    }
+
+    async fn meta_set_multi<'a, K, V>(
+        &mut self,
+        items: &'a [MetaSetItem<K, V>],
+        meta_flags: Option<&[&str]>,
+    ) -> Result<FxHashMap<&'a K, Result<(), Error>>, Error>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash,
+        V: AsMemcachedValue,
+    {
+        let shards = self.partition(items, |(key, ..)| key.as_ref());
+
+        let shard_results = join_all(
+            self.clients
+                .iter_mut()
+                .zip(shards)
+                .filter(|(_, items)| !items.is_empty())
+                .map(|(client, items)| client.meta_set_multi_command(items, meta_flags)),
+        )
+        .await;
+
+        let mut results = FxHashMap::with_capacity_and_hasher(items.len(), Default::default());
+        for shard_result in shard_results {
+            results.extend(shard_result?);
+        }
+
+        Ok(results)
+    }
+
+    async fn meta_delete<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        meta_flags: Option<&[&str]>,
+    ) -> Result<Option<MetaValue>, Error> {
+        self.client_for_key(key.as_ref())
+            .meta_delete(key, is_quiet, opaque, meta_flags)
+            .await
+    }
+
+    async fn meta_delete_with<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        flags: &MetaDeleteFlags,
+    ) -> Result<Option<MetaValue>, Error> {
+        self.client_for_key(key.as_ref())
+            .meta_delete_with(key, is_quiet, opaque, flags)
+            .await
+    }
+
+    async fn meta_delete_multi<'a, K>(
+        &mut self,
+        keys: &'a [K],
+        meta_flags: Option<&[&str]>,
+    ) -> Result<FxHashMap<&'a K, Result<(), Error>>, Error>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash,
+    {
+        let shards = self.partition(keys, |key| key.as_ref());
+
+        let shard_results = join_all(
+            self.clients
+                .iter_mut()
+                .zip(shards)
+                .filter(|(_, keys)| !keys.is_empty())
+                .map(|(client, keys)| client.meta_delete_multi_command(keys, meta_flags)),
+        )
+        .await;
+
+        let mut results = FxHashMap::with_capacity_and_hasher(keys.len(), Default::default());
+        for shard_result in shard_results {
+            results.extend(shard_result?);
+        }
+
+        Ok(results)
+    }
+
+    async fn meta_increment<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        delta: Option<u64>,
+        meta_flags: Option<&[&str]>,
+    ) -> Result<Option<MetaValue>, Error> {
+        self.client_for_key(key.as_ref())
+            .meta_increment(key, is_quiet, opaque, delta, meta_flags)
+            .await
+    }
+
+    async fn meta_increment_with<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        delta: Option<u64>,
+        flags: &MetaArithmeticFlags,
+    ) -> Result<Option<MetaValue>, Error> {
+        self.client_for_key(key.as_ref())
+            .meta_increment_with(key, is_quiet, opaque, delta, flags)
+            .await
+    }
+
+    async fn meta_decrement<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        delta: Option<u64>,
+        meta_flags: Option<&[&str]>,
+    ) -> Result<Option<MetaValue>, Error> {
+        self.client_for_key(key.as_ref())
+            .meta_decrement(key, is_quiet, opaque, delta, meta_flags)
+            .await
+    }
+
+    async fn meta_decrement_with<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        delta: Option<u64>,
+        flags: &MetaArithmeticFlags,
+    ) -> Result<Option<MetaValue>, Error> {
+        self.client_for_key(key.as_ref())
+            .meta_decrement_with(key, is_quiet, opaque, delta, flags)
+            .await
+    }
+
+    async fn meta_noop(&mut self) -> Result<(), Error> {
+        join_all(self.clients.iter_mut().map(|client| client.meta_noop()))
+            .await
+            .into_iter()
+            .collect()
+    }
+
+    async fn meta_debug<K: AsRef<[u8]>>(&mut self, key: K) -> Result<Option<MetaDebugInfo>, Error> {
+        self.client_for_key(key.as_ref()).meta_debug(key).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{server_identity, Cluster, Continuum};
+    use crate::KeyPolicy;
+
+    fn cluster(key_policy: Option<KeyPolicy>) -> Cluster {
+        let identities: Vec<_> = (1..=4)
+            .map(|i| (format!("10.0.1.{}", i), 11211, 1))
+            .collect();
+
+        Cluster {
+            clients: Vec::new(),
+            continuum: Continuum::new(&identities),
+            key_policy,
+        }
+    }
+
+    #[test]
+    fn test_keys_are_placed_by_the_key_sent() {
+        let policy = KeyPolicy::default();
+        let with_policy = cluster(Some(policy.clone()));
+        let without_policy = cluster(None);
+
+        for i in 0..32 {
+            let long_key = format!("{}{}", i, "a".repeat(250));
+            assert_eq!(
+                with_policy.server_for(long_key.as_bytes()),
+                with_policy
+                    .continuum
+                    .server_for(&policy.hash_key(long_key.as_bytes()))
+            );
+            assert_eq!(
+                without_policy.server_for(long_key.as_bytes()),
+                without_policy.continuum.server_for(long_key.as_bytes())
+            );
+
+            let key = format!("key-{}", i);
+            assert_eq!(
+                with_policy.server_for(key.as_bytes()),
+                with_policy.continuum.server_for(key.as_bytes())
+            );
+        }
+    }
+
+    #[test]
+    fn test_server_identity_for_tcp_dsn() {
+        assert_eq!(
+            server_identity("tcp://10.0.1.1:11211").unwrap(),
+            ("10.0.1.1".to_string(), 11211)
+        );
+        assert_eq!(
+            server_identity("tcp://cache.internal").unwrap(),
+            ("cache.internal".to_string(), 11211)
+        );
+        assert_eq!(
+            server_identity("tcp://10.0.1.2:11212").unwrap(),
+            ("10.0.1.2".to_string(), 11212)
+        );
+    }
+
+    #[test]
+    fn test_server_identity_for_tls_dsn() {
+        assert_eq!(
+            server_identity("tls://10.0.1.1:11211").unwrap(),
+            ("10.0.1.1".to_string(), 11211)
+        );
+        assert_eq!(
+            server_identity("tls://cache.internal").unwrap(),
+            ("cache.internal".to_string(), 11211)
+        );
+        assert_eq!(
+            server_identity("tcp+tls://10.0.1.2:11212").unwrap(),
+            ("10.0.1.2".to_string(), 11212)
+        );
+        assert_eq!(
+            server_identity("tcp+tls://cache.internal").unwrap(),
+            ("cache.internal".to_string(), 11211)
+        );
+    }
+
+    #[test]
+    fn test_server_identity_for_ipv6_dsn() {
+        assert_eq!(
+            server_identity("tcp://[::1]:11211").unwrap(),
+            ("::1".to_string(), 11211)
+        );
+    }
+
+    #[test]
+    fn test_server_identity_for_unix_dsn() {
+        assert_eq!(
+            server_identity("unix:///tmp/memcached.sock").unwrap(),
+            ("/tmp/memcached.sock".to_string(), 0)
+        );
+    }
+
+    #[test]
+    fn test_server_identity_for_dsn_without_scheme() {
+        assert_eq!(
+            server_identity("localhost:11211").unwrap(),
+            ("localhost".to_string(), 11211)
+        );
+        assert_eq!(
+            server_identity("[::1]:11211").unwrap(),
+            ("::1".to_string(), 11211)
+        );
+        assert!(server_identity("localhost:port").is_err());
+    }
+}
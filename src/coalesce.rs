@@ -0,0 +1,439 @@
+use std::collections::HashMap;
+use std::io;
+use std::time::Duration;
+
+use futures::future::join_all;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{timeout_at, Instant};
+
+use crate::{has_binary_key_flag, Error, MetaValue, MultiplexedClient, Status, Value};
+
+/// Configuration for a [`CoalescingClient`].
+#[derive(Clone, Debug)]
+pub struct CoalescingConfig {
+    /// How long to wait for more lookups after the first lookup of a batch arrives.
+    pub window: Duration,
+    /// Maximum number of distinct lookups in a batch.  A batch is sent as soon as it is full,
+    /// without waiting for the window to elapse.
+    pub max_batch_size: usize,
+}
+
+impl Default for CoalescingConfig {
+    fn default() -> Self {
+        CoalescingConfig {
+            window: Duration::from_millis(1),
+            max_batch_size: 100,
+        }
+    }
+}
+
+type GetReply = oneshot::Sender<Result<Option<Value>, Error>>;
+type MetaGetReply = oneshot::Sender<Result<Option<MetaValue>, Error>>;
+
+enum Lookup {
+    Get {
+        key: Vec<u8>,
+        reply: GetReply,
+    },
+    MetaGet {
+        key: Vec<u8>,
+        meta_flags: Vec<String>,
+        reply: MetaGetReply,
+    },
+}
+
+// Lookups collected within one window.  Identical lookups are only sent to the server once, and
+// their result is shared by every caller.
+#[derive(Default)]
+struct Batch {
+    gets: HashMap<Vec<u8>, Vec<GetReply>>,
+    meta_gets: HashMap<(Vec<u8>, Vec<String>), Vec<MetaGetReply>>,
+}
+
+impl Batch {
+    fn len(&self) -> usize {
+        self.gets.len() + self.meta_gets.len()
+    }
+
+    fn push(&mut self, lookup: Lookup) {
+        match lookup {
+            Lookup::Get { key, reply } => self.gets.entry(key).or_default().push(reply),
+            Lookup::MetaGet {
+                key,
+                meta_flags,
+                reply,
+            } => self
+                .meta_gets
+                .entry((key, meta_flags))
+                .or_default()
+                .push(reply),
+        }
+    }
+
+    // Sends all plain gets as a single `get_multi`, and all meta gets pipelined alongside it, then
+    // fans the results back out to their callers.
+    async fn execute(self, client: MultiplexedClient) {
+        let Batch { gets, meta_gets } = self;
+        let client = &client;
+
+        let get_multi = async move {
+            if gets.is_empty() {
+                return;
+            }
+
+            let result = client.get_multi(gets.keys()).await;
+            let mut values: HashMap<Vec<u8>, Value> = match result {
+                Ok(values) => values.into_iter().map(|v| (v.key.clone(), v)).collect(),
+                Err(Error::Protocol(Status::NotFound)) => HashMap::new(),
+                Err(e) => {
+                    for reply in gets.into_values().flatten() {
+                        let _ = reply.send(Err(duplicate(&e)));
+                    }
+                    return;
+                }
+            };
+
+            for (key, replies) in gets {
+                let value = values.remove(&key);
+                for reply in replies {
+                    let _ = reply.send(Ok(value.clone()));
+                }
+            }
+        };
+
+        let meta_gets = join_all(meta_gets.into_iter().map(
+            |((key, meta_flags), replies)| async move {
+                let flags: Vec<&str> = meta_flags.iter().map(String::as_str).collect();
+                let result = client.meta_get(&key, None, Some(&flags)).await;
+
+                for reply in replies {
+                    let _ = reply.send(match &result {
+                        Ok(value) => Ok(value.clone()),
+                        Err(e) => Err(duplicate(e)),
+                    });
+                }
+            },
+        ));
+
+        futures::future::join(get_multi, meta_gets).await;
+    }
+}
+
+/// Batching layer that coalesces concurrent single-key lookups into fewer requests.
+///
+/// Lookups that arrive within [`CoalescingConfig::window`] of the first lookup of a batch, up to
+/// [`CoalescingConfig::max_batch_size`] of them, are sent together: every [`get`](Self::get) as
+/// one `get_multi`, and every [`meta_get`](Self::meta_get) pipelined alongside it.  Identical
+/// lookups within a batch are sent once, and their result is shared.
+///
+/// This trades a small amount of latency, at most the window, for far fewer round-trips when many
+/// tasks look up keys concurrently.  The handle can be cloned and shared between tasks, and the
+/// background task that collects batches stops once every clone has been dropped.
+#[derive(Clone, Debug)]
+pub struct CoalescingClient {
+    client: MultiplexedClient,
+    lookups: mpsc::Sender<Lookup>,
+}
+
+impl CoalescingClient {
+    /// Creates a new [`CoalescingClient`] that sends its batches through the given client.
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub fn new(client: MultiplexedClient, config: CoalescingConfig) -> CoalescingClient {
+        let (lookups_tx, lookups_rx) = mpsc::channel(config.max_batch_size.max(1) * 4);
+        tokio::spawn(collect_batches(client.clone(), config, lookups_rx));
+
+        CoalescingClient {
+            client,
+            lookups: lookups_tx,
+        }
+    }
+
+    /// Gets the given key, as part of the next batch.
+    ///
+    /// If the key is found, `Some(Value)` is returned, describing the metadata and data of the key.
+    ///
+    /// Otherwise, `None` is returned.
+    pub async fn get<K: AsRef<[u8]>>(&self, key: K) -> Result<Option<Value>, Error> {
+        // Invalid keys fail straight away, unless the key policy of the client rewrites them.
+        self.client.prepare_key(key.as_ref(), false)?;
+        let key = key.as_ref().to_vec();
+
+        let (reply, result) = oneshot::channel();
+        self.lookup(Lookup::Get { key, reply }, result).await
+    }
+
+    /// Gets the given key with additional metadata, as part of the next batch.
+    ///
+    /// If the key is found, `Some(MetaValue)` is returned, describing the metadata and data of the
+    /// key, as requested by the meta flags.
+    ///
+    /// Otherwise, `None` is returned.
+    pub async fn meta_get<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        meta_flags: Option<&[&str]>,
+    ) -> Result<Option<MetaValue>, Error> {
+        self.client
+            .prepare_key(key.as_ref(), has_binary_key_flag(meta_flags))?;
+        let key = key.as_ref().to_vec();
+        let meta_flags = meta_flags
+            .unwrap_or_default()
+            .iter()
+            .map(|flag| flag.to_string())
+            .collect();
+
+        let (reply, result) = oneshot::channel();
+        let lookup = Lookup::MetaGet {
+            key,
+            meta_flags,
+            reply,
+        };
+        self.lookup(lookup, result).await
+    }
+
+    async fn lookup<T>(
+        &self,
+        lookup: Lookup,
+        result: oneshot::Receiver<Result<T, Error>>,
+    ) -> Result<T, Error> {
+        self.lookups.send(lookup).await.map_err(|_| closed())?;
+        result.await.map_err(|_| closed())?
+    }
+}
+
+fn closed() -> Error {
+    Error::Io(io::Error::new(
+        io::ErrorKind::NotConnected,
+        "coalescing client is closed",
+    ))
+}
+
+// Errors are not `Clone`, as I/O errors may carry arbitrary payloads, so each caller sharing a
+// failed lookup receives an equivalent error instead.
+fn duplicate(e: &Error) -> Error {
+    match e {
+        Error::Connect(e) => Error::Connect(io::Error::new(e.kind(), e.to_string())),
+        Error::Io(e) => Error::Io(io::Error::new(e.kind(), e.to_string())),
+        Error::Protocol(s) => Error::Protocol(s.clone()),
+        Error::ParseError(kind) => Error::ParseError(*kind),
+        Error::Timeout => Error::Timeout,
+        Error::Poisoned => Error::Poisoned,
+        Error::Authentication(message) => Error::Authentication(message.clone()),
+    }
+}
+
+async fn collect_batches(
+    client: MultiplexedClient,
+    config: CoalescingConfig,
+    mut lookups: mpsc::Receiver<Lookup>,
+) {
+    while let Some(lookup) = lookups.recv().await {
+        let mut batch = Batch::default();
+        batch.push(lookup);
+
+        let deadline = Instant::now() + config.window;
+        while batch.len() < config.max_batch_size {
+            match timeout_at(deadline, lookups.recv()).await {
+                Ok(Some(lookup)) => batch.push(lookup),
+                Ok(None) | Err(_) => break,
+            }
+        }
+
+        tokio::spawn(batch.execute(client.clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CoalescingClient, CoalescingConfig};
+    use crate::{ClientConfig, Error, ErrorKind, KeyPolicy, MultiplexedClient, Status};
+
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    // Accepts a single connection and records every command it receives.  Each `get` is answered
+    // with a value, equal to the key, for every requested key that doesn't start with `missing`.
+    // Each `mg` is answered with a hit whose value is the key.
+    async fn recording_server() -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let commands = Arc::new(Mutex::new(Vec::new()));
+        let recorded = commands.clone();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut stream = BufReader::new(stream);
+
+            loop {
+                let mut line = String::new();
+                if stream.read_line(&mut line).await.unwrap_or(0) == 0 {
+                    return;
+                }
+                let line = line.trim_end().to_string();
+                recorded.lock().unwrap().push(line.clone());
+
+                let mut parts = line.split(' ');
+                let response = match parts.next() {
+                    Some("get") => {
+                        let mut response = String::new();
+                        for key in parts.filter(|key| !key.starts_with("missing")) {
+                            response.push_str(&format!(
+                                "VALUE {} 0 {}\r\n{}\r\n",
+                                key,
+                                key.len(),
+                                key
+                            ));
+                        }
+                        response + "END\r\n"
+                    }
+                    Some("mg") => {
+                        let key = parts.next().unwrap();
+                        format!("VA {} k{}\r\n{}\r\n", key.len(), key, key)
+                    }
+                    _ => "ERROR\r\n".to_string(),
+                };
+                stream
+                    .get_mut()
+                    .write_all(response.as_bytes())
+                    .await
+                    .unwrap();
+            }
+        });
+
+        (format!("tcp://{}", addr), commands)
+    }
+
+    async fn coalescing_client(
+        config: CoalescingConfig,
+    ) -> (CoalescingClient, Arc<Mutex<Vec<String>>>) {
+        let (dsn, commands) = recording_server().await;
+        let client = MultiplexedClient::new(dsn).await.unwrap();
+
+        (CoalescingClient::new(client, config), commands)
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_gets_are_coalesced_and_deduplicated() {
+        let (client, commands) = coalescing_client(CoalescingConfig {
+            window: Duration::from_millis(50),
+            max_batch_size: 100,
+        })
+        .await;
+
+        let keys = ["a", "b", "a", "missing-c"];
+        let results = futures::future::join_all(keys.iter().map(|key| client.get(key))).await;
+
+        for (key, result) in keys.iter().zip(results) {
+            let value = result.unwrap();
+            if key.starts_with("missing") {
+                assert_eq!(value, None);
+            } else {
+                assert_eq!(value.unwrap().data, Some(key.as_bytes().to_vec()));
+            }
+        }
+
+        let commands = commands.lock().unwrap();
+        assert_eq!(commands.len(), 1);
+
+        let mut requested: Vec<&str> = commands[0].split(' ').skip(1).collect();
+        requested.sort();
+        assert_eq!(requested, ["a", "b", "missing-c"]);
+    }
+
+    #[tokio::test]
+    async fn test_full_batch_is_sent_without_waiting_for_window() {
+        let (client, commands) = coalescing_client(CoalescingConfig {
+            window: Duration::from_secs(60),
+            max_batch_size: 2,
+        })
+        .await;
+
+        let (a, b) = tokio::time::timeout(
+            Duration::from_secs(5),
+            futures::future::join(client.get("a"), client.get("b")),
+        )
+        .await
+        .unwrap();
+
+        assert!(a.unwrap().is_some());
+        assert!(b.unwrap().is_some());
+        assert_eq!(commands.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_identical_meta_gets_are_deduplicated() {
+        let (client, commands) = coalescing_client(CoalescingConfig {
+            window: Duration::from_millis(50),
+            max_batch_size: 100,
+        })
+        .await;
+
+        let flags = ["v", "k"];
+        let (a, b, c) = futures::future::join3(
+            client.meta_get("a", Some(&flags)),
+            client.meta_get("a", Some(&flags)),
+            client.meta_get("b", Some(&flags)),
+        )
+        .await;
+
+        assert_eq!(a.unwrap().unwrap().data, Some(b"a".to_vec()));
+        assert_eq!(b.unwrap().unwrap().data, Some(b"a".to_vec()));
+        assert_eq!(c.unwrap().unwrap().data, Some(b"b".to_vec()));
+
+        let mut commands = commands.lock().unwrap().clone();
+        commands.sort();
+        assert_eq!(commands, ["mg a v k", "mg b v k"]);
+    }
+
+    #[tokio::test]
+    async fn test_key_policy_applies_to_lookups() {
+        let (dsn, commands) = recording_server().await;
+        let config = ClientConfig {
+            key_policy: Some(KeyPolicy::default()),
+            ..Default::default()
+        };
+        let client = MultiplexedClient::with_config(dsn, config).await.unwrap();
+        let client = CoalescingClient::new(client, CoalescingConfig::default());
+
+        let long_key = "k".repeat(300);
+        let value = client.get(&long_key).await.unwrap().unwrap();
+        assert_eq!(value.key, long_key.as_bytes());
+
+        let value = client.meta_get(&long_key, Some(&["v", "k"])).await;
+        assert_eq!(value.unwrap().unwrap().key, Some(long_key.into_bytes()));
+
+        let commands = commands.lock().unwrap();
+        assert_eq!(commands.len(), 2);
+        assert!(commands[0].starts_with("get sha256:"));
+        assert!(commands[1].starts_with("mg sha256:"));
+    }
+
+    #[tokio::test]
+    async fn test_meta_get_with_binary_key() {
+        let (client, commands) = coalescing_client(CoalescingConfig::default()).await;
+
+        let key = [0u8, 255, b' ', b'\n'];
+        let value = client.meta_get(key, Some(&["b", "k", "v"])).await;
+        assert_eq!(value.unwrap().unwrap().key, Some(key.to_vec()));
+
+        assert_eq!(*commands.lock().unwrap(), ["mg AP8gCg== b k v"]);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_keys_fail_without_key_policy() {
+        let (client, commands) = coalescing_client(CoalescingConfig::default()).await;
+
+        assert_eq!(
+            client.get("k".repeat(300)).await,
+            Err(Error::Protocol(Status::Error(ErrorKind::KeyTooLong)))
+        );
+        assert_eq!(
+            client.meta_get("foo bar", None).await,
+            Err(Error::Protocol(Status::Error(ErrorKind::InvalidKey)))
+        );
+        assert!(commands.lock().unwrap().is_empty());
+    }
+}
@@ -1,17 +1,82 @@
 use pin_project::pin_project;
+use std::future::Future;
 use std::io;
+use std::net::SocketAddr;
 use std::pin::Pin;
 use std::task::{Context, Poll};
+use std::time::Duration;
 use tokio::io::{AsyncBufRead, AsyncRead, AsyncWrite, BufReader, BufWriter};
 use tokio::net::{lookup_host, TcpSocket, TcpStream, UnixStream};
+use tokio::time::{sleep_until, Instant, Sleep};
 
-use crate::Error;
+use futures::stream::{FuturesUnordered, StreamExt};
 
-#[pin_project(project = ConnectionProjection)]
+use crate::auth::{self, Credentials};
+use crate::{ClientConfig, Error};
+
+// Delay before starting the next connection attempt while earlier ones are still pending, as
+// recommended by RFC 8305.
+const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+#[pin_project(project = StreamProjection)]
 #[derive(Debug)]
-pub enum Connection {
+enum Stream {
     Tcp(#[pin] BufReader<BufWriter<TcpStream>>),
     Unix(#[pin] BufReader<BufWriter<UnixStream>>),
+    #[cfg(feature = "tls")]
+    Tls(#[pin] BufReader<BufWriter<Box<tokio_rustls::client::TlsStream<TcpStream>>>>),
+}
+
+/// A buffered connection to a memcached server.
+///
+/// Reads and writes fail with an [`io::Error`] of kind [`io::ErrorKind::TimedOut`] when they make
+/// no progress within the configured read or write timeout, or when the deadline for the current
+/// request passes.
+#[pin_project]
+#[derive(Debug)]
+pub struct Connection {
+    #[pin]
+    stream: Stream,
+    read_timeout: Option<Duration>,
+    write_timeout: Option<Duration>,
+    deadline: Option<Instant>,
+    read_timer: Option<Pin<Box<Sleep>>>,
+    write_timer: Option<Pin<Box<Sleep>>>,
+}
+
+// Bounds how long an I/O operation may remain pending.  The timer is armed the first time the
+// operation returns `Pending`, and disarmed whenever it makes progress.
+fn poll_timeout<T>(
+    poll: Poll<io::Result<T>>,
+    cx: &mut Context<'_>,
+    timer: &mut Option<Pin<Box<Sleep>>>,
+    timeout: Option<Duration>,
+    deadline: Option<Instant>,
+) -> Poll<io::Result<T>> {
+    if poll.is_ready() {
+        *timer = None;
+        return poll;
+    }
+
+    if timer.is_none() {
+        let expiry = match (timeout.map(|t| Instant::now() + t), deadline) {
+            (Some(a), Some(b)) => a.min(b),
+            (Some(a), None) | (None, Some(a)) => a,
+            (None, None) => return Poll::Pending,
+        };
+        *timer = Some(Box::pin(sleep_until(expiry)));
+    }
+
+    match timer.as_mut().map(|t| t.as_mut().poll(cx)) {
+        Some(Poll::Ready(())) => {
+            *timer = None;
+            Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "operation timed out",
+            )))
+        }
+        _ => Poll::Pending,
+    }
 }
 
 impl AsyncRead for Connection {
@@ -20,123 +85,343 @@ impl AsyncRead for Connection {
         cx: &mut Context<'_>,
         buf: &mut tokio::io::ReadBuf<'_>,
     ) -> Poll<io::Result<()>> {
-        match self.project() {
-            ConnectionProjection::Tcp(s) => s.poll_read(cx, buf),
-            ConnectionProjection::Unix(s) => s.poll_read(cx, buf),
-        }
+        let this = self.project();
+        let poll = match this.stream.project() {
+            StreamProjection::Tcp(s) => s.poll_read(cx, buf),
+            StreamProjection::Unix(s) => s.poll_read(cx, buf),
+            #[cfg(feature = "tls")]
+            StreamProjection::Tls(s) => s.poll_read(cx, buf),
+        };
+        poll_timeout(
+            poll,
+            cx,
+            this.read_timer,
+            *this.read_timeout,
+            *this.deadline,
+        )
     }
 }
 
 impl AsyncWrite for Connection {
     fn poll_write(self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<io::Result<usize>> {
-        match self.project() {
-            ConnectionProjection::Tcp(s) => s.poll_write(cx, buf),
-            ConnectionProjection::Unix(s) => s.poll_write(cx, buf),
-        }
+        let this = self.project();
+        let poll = match this.stream.project() {
+            StreamProjection::Tcp(s) => s.poll_write(cx, buf),
+            StreamProjection::Unix(s) => s.poll_write(cx, buf),
+            #[cfg(feature = "tls")]
+            StreamProjection::Tls(s) => s.poll_write(cx, buf),
+        };
+        poll_timeout(
+            poll,
+            cx,
+            this.write_timer,
+            *this.write_timeout,
+            *this.deadline,
+        )
     }
 
     fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
-        match self.project() {
-            ConnectionProjection::Tcp(s) => s.poll_flush(cx),
-            ConnectionProjection::Unix(s) => s.poll_flush(cx),
-        }
+        let this = self.project();
+        let poll = match this.stream.project() {
+            StreamProjection::Tcp(s) => s.poll_flush(cx),
+            StreamProjection::Unix(s) => s.poll_flush(cx),
+            #[cfg(feature = "tls")]
+            StreamProjection::Tls(s) => s.poll_flush(cx),
+        };
+        poll_timeout(
+            poll,
+            cx,
+            this.write_timer,
+            *this.write_timeout,
+            *this.deadline,
+        )
     }
 
     fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
-        match self.project() {
-            ConnectionProjection::Tcp(s) => s.poll_shutdown(cx),
-            ConnectionProjection::Unix(s) => s.poll_shutdown(cx),
-        }
+        let this = self.project();
+        let poll = match this.stream.project() {
+            StreamProjection::Tcp(s) => s.poll_shutdown(cx),
+            StreamProjection::Unix(s) => s.poll_shutdown(cx),
+            #[cfg(feature = "tls")]
+            StreamProjection::Tls(s) => s.poll_shutdown(cx),
+        };
+        poll_timeout(
+            poll,
+            cx,
+            this.write_timer,
+            *this.write_timeout,
+            *this.deadline,
+        )
     }
 }
 
 impl AsyncBufRead for Connection {
     fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<&[u8]>> {
-        match self.project() {
-            ConnectionProjection::Tcp(s) => s.poll_fill_buf(cx),
-            ConnectionProjection::Unix(s) => s.poll_fill_buf(cx),
-        }
+        let this = self.project();
+        let poll = match this.stream.project() {
+            StreamProjection::Tcp(s) => s.poll_fill_buf(cx),
+            StreamProjection::Unix(s) => s.poll_fill_buf(cx),
+            #[cfg(feature = "tls")]
+            StreamProjection::Tls(s) => s.poll_fill_buf(cx),
+        };
+        poll_timeout(
+            poll,
+            cx,
+            this.read_timer,
+            *this.read_timeout,
+            *this.deadline,
+        )
     }
 
     fn consume(self: Pin<&mut Self>, amt: usize) {
-        match self.project() {
-            ConnectionProjection::Tcp(s) => s.consume(amt),
-            ConnectionProjection::Unix(s) => s.consume(amt),
+        match self.project().stream.project() {
+            StreamProjection::Tcp(s) => s.consume(amt),
+            StreamProjection::Unix(s) => s.consume(amt),
+            #[cfg(feature = "tls")]
+            StreamProjection::Tls(s) => s.consume(amt),
         }
     }
 }
 
 #[derive(Debug, PartialEq)]
-enum Addr {
+pub(crate) enum Addr {
     Tcp(String),
+    Udp(String),
     Unix(String),
+    // The address to connect to, and the host name the server's certificate is verified against.
+    Tls(String, String),
     Unknown(String),
 }
 
 impl Addr {
     const DEFAULT_PORT: u16 = 11211;
 
-    fn parse(dsn: &str) -> Result<Self, Error> {
-        let url = url::Url::parse(dsn).map_err(|e| {
-            Error::Connect(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                format!("failed to parse DSN: {}", e),
-            ))
-        })?;
+    pub(crate) fn parse(dsn: &str) -> Result<Self, Error> {
+        let url = match url::Url::parse(dsn) {
+            Ok(url) => url,
+            // DSNs without a scheme that don't begin with a letter, such as `127.0.0.1:11211` or
+            // `[::1]:11211`, are left for the resolver to interpret.
+            Err(url::ParseError::RelativeUrlWithoutBase) => {
+                return Ok(Addr::Unknown(dsn.to_string()))
+            }
+            Err(e) => {
+                return Err(Error::Connect(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("failed to parse DSN: {}", e),
+                )))
+            }
+        };
 
         match url.scheme() {
             "unix" => Ok(Addr::Unix(url.path().to_string())),
-            "tcp" => Ok(Addr::Tcp(format!(
-                "{}:{}",
-                url.host_str().ok_or_else(|| {
-                    Error::Connect(io::Error::new(
-                        io::ErrorKind::InvalidInput,
-                        "no host found in DSN",
-                    ))
-                })?,
-                url.port().unwrap_or(Self::DEFAULT_PORT)
-            ))),
+            "tcp" => Ok(Addr::Tcp(Self::host_and_port(&url)?)),
+            "udp" => Ok(Addr::Udp(Self::host_and_port(&url)?)),
+            "tls" | "tcp+tls" => {
+                let host = match url.host() {
+                    Some(url::Host::Ipv6(ip)) => ip.to_string(),
+                    Some(host) => host.to_string(),
+                    None => return Err(Self::missing_host()),
+                };
+                Ok(Addr::Tls(Self::host_and_port(&url)?, host))
+            }
             _ => Ok(Addr::Unknown(dsn.to_string())),
         }
     }
+
+    fn host_and_port(url: &url::Url) -> Result<String, Error> {
+        Ok(format!(
+            "{}:{}",
+            url.host_str().ok_or_else(Self::missing_host)?,
+            url.port().unwrap_or(Self::DEFAULT_PORT)
+        ))
+    }
+
+    fn missing_host() -> Error {
+        Error::Connect(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "no host found in DSN",
+        ))
+    }
+}
+
+// Connects to the first address that accepts a connection, in the style of Happy Eyeballs (RFC
+// 8305).  Attempts are started in order, with the next one started whenever the previous attempt
+// fails or has not completed within the connection attempt delay, and the first to succeed wins.
+async fn connect_happy_eyeballs(addrs: Vec<SocketAddr>) -> Result<TcpStream, Error> {
+    let mut candidates = interleave_families(addrs).into_iter();
+    let mut attempts = FuturesUnordered::new();
+    let mut last_err = None;
+
+    loop {
+        match candidates.next() {
+            Some(addr) => attempts.push(connect_addr(addr)),
+            None if attempts.is_empty() => break,
+            None => {}
+        }
+
+        let result = if candidates.len() > 0 {
+            match tokio::time::timeout(CONNECTION_ATTEMPT_DELAY, attempts.next()).await {
+                Ok(result) => result,
+                // Give up waiting on the pending attempts, and start another alongside them.
+                Err(_) => continue,
+            }
+        } else {
+            attempts.next().await
+        };
+
+        match result {
+            Some(Ok(stream)) => return Ok(stream),
+            Some(Err(e)) => last_err = Some(e),
+            None => {}
+        }
+    }
+
+    Err(Error::Connect(last_err.unwrap_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "could not resolve to any address",
+        )
+    })))
+}
+
+async fn connect_addr(addr: SocketAddr) -> io::Result<TcpStream> {
+    let socket = match addr {
+        SocketAddr::V4(_) => TcpSocket::new_v4()?,
+        SocketAddr::V6(_) => TcpSocket::new_v6()?,
+    };
+    socket.set_nodelay(true)?;
+    socket.connect(addr).await
+}
+
+// Orders addresses so that the two families alternate, starting with the family of the first
+// address returned by the resolver.
+fn interleave_families(addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let prefer_v6 = addrs.first().is_some_and(|addr| addr.is_ipv6());
+    let (preferred, other): (Vec<_>, Vec<_>) = addrs
+        .into_iter()
+        .partition(|addr| addr.is_ipv6() == prefer_v6);
+
+    let mut interleaved = Vec::with_capacity(preferred.len() + other.len());
+    let mut preferred = preferred.into_iter();
+    let mut other = other.into_iter();
+
+    loop {
+        match (preferred.next(), other.next()) {
+            (None, None) => break,
+            (a, b) => interleaved.extend(a.into_iter().chain(b)),
+        }
+    }
+
+    interleaved
 }
 
 impl Connection {
-    pub async fn new<S: AsRef<str>>(dsn: S) -> Result<Self, Error> {
-        match Addr::parse(dsn.as_ref())? {
+    pub async fn new<S: AsRef<str>>(dsn: S, config: &ClientConfig) -> Result<Self, Error> {
+        match config.connect_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, Self::establish(dsn.as_ref(), config))
+                .await
+                .map_err(|_| Error::Timeout)?,
+            None => Self::establish(dsn.as_ref(), config).await,
+        }
+    }
+
+    // Connects to the server and authenticates, if credentials were given in the configuration or
+    // the DSN.
+    async fn establish(dsn: &str, config: &ClientConfig) -> Result<Self, Error> {
+        let mut conn = Connection {
+            stream: Self::connect(dsn, config).await?,
+            read_timeout: config.read_timeout,
+            write_timeout: config.write_timeout,
+            deadline: None,
+            read_timer: None,
+            write_timer: None,
+        };
+
+        let credentials = config
+            .credentials
+            .clone()
+            .or_else(|| Credentials::from_dsn(dsn));
+        if let Some(credentials) = credentials {
+            auth::authenticate(&mut conn, &credentials).await?;
+        }
+
+        Ok(conn)
+    }
+
+    #[cfg_attr(not(feature = "tls"), allow(unused_variables))]
+    async fn connect(dsn: &str, config: &ClientConfig) -> Result<Stream, Error> {
+        match Addr::parse(dsn)? {
             Addr::Unix(path) => UnixStream::connect(path)
                 .await
-                .map(|c| Connection::Unix(BufReader::new(BufWriter::new(c))))
+                .map(|c| Stream::Unix(BufReader::new(BufWriter::new(c))))
                 .map_err(Error::Connect),
             Addr::Tcp(url) | Addr::Unknown(url) => {
                 let addrs = lookup_host(url).await.map_err(Error::Connect)?;
 
-                let mut last_err = None;
-
-                for addr in addrs {
-                    let socket = TcpSocket::new_v4().map_err(Error::Connect)?;
-                    socket.set_nodelay(true).map_err(Error::Connect)?;
-                    match socket.connect(addr).await {
-                        Ok(stream) => {
-                            return Ok(Connection::Tcp(BufReader::new(BufWriter::new(stream))))
-                        }
-                        Err(e) => last_err = Some(Error::Connect(e)),
-                    }
-                }
-                Err(last_err.unwrap_or_else(|| {
-                    Error::Connect(io::Error::new(
-                        io::ErrorKind::InvalidInput,
-                        "could not resolve to any address",
-                    ))
-                }))
+                connect_happy_eyeballs(addrs.collect())
+                    .await
+                    .map(|stream| Stream::Tcp(BufReader::new(BufWriter::new(stream))))
+            }
+            Addr::Udp(_) => Err(Error::Connect(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "UDP is only supported by `UdpClient`",
+            ))),
+            #[cfg(feature = "tls")]
+            Addr::Tls(url, host) => {
+                let addrs = lookup_host(url).await.map_err(Error::Connect)?;
+                let stream = connect_happy_eyeballs(addrs.collect()).await?;
+
+                crate::tls::connect(stream, &host, &config.tls)
+                    .await
+                    .map(|stream| Stream::Tls(BufReader::new(BufWriter::new(Box::new(stream)))))
             }
+            #[cfg(not(feature = "tls"))]
+            Addr::Tls(..) => Err(Error::Connect(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "TLS support requires the `tls` feature",
+            ))),
         }
     }
+
+    /// Sets the deadline by which all I/O for the current request must complete.
+    ///
+    /// Any timers left armed by a previous request are discarded.
+    pub fn set_deadline(&mut self, deadline: Option<Instant>) {
+        self.deadline = deadline;
+        self.read_timer = None;
+        self.write_timer = None;
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Addr, Error};
+    use super::{connect_happy_eyeballs, interleave_families, Addr, Connection, Error};
+    use crate::ClientConfig;
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::time::Instant;
+
+    // Accepts a single connection and holds it open without ever responding.
+    async fn silent_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (_stream, _) = listener.accept().await.unwrap();
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        });
+
+        format!("tcp://{}", addr)
+    }
+
+    async fn read_response(conn: &mut Connection) -> std::io::Result<usize> {
+        conn.write_all(b"get foo\r\n").await?;
+        conn.flush().await?;
+
+        let mut buf = Vec::new();
+        conn.read_buf(&mut buf).await
+    }
 
     #[tokio::test]
     async fn test_unknown_scheme() {
@@ -154,6 +439,14 @@ mod tests {
         )
     }
 
+    #[tokio::test]
+    async fn test_udp_scheme() {
+        assert_eq!(
+            Addr::parse("udp://localhost:11211"),
+            Ok(Addr::Udp("localhost:11211".to_string()))
+        )
+    }
+
     #[tokio::test]
     async fn test_unix_scheme() {
         assert_eq!(
@@ -162,6 +455,55 @@ mod tests {
         )
     }
 
+    #[tokio::test]
+    async fn test_tcp_scheme_with_bracketed_ipv6() {
+        assert_eq!(
+            Addr::parse("tcp://[::1]:11211"),
+            Ok(Addr::Tcp("[::1]:11211".to_string()))
+        );
+        assert_eq!(
+            Addr::parse("tcp://[2001:db8::1]"),
+            Ok(Addr::Tcp("[2001:db8::1]:11211".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tls_scheme() {
+        assert_eq!(
+            Addr::parse("tls://cache.example.com"),
+            Ok(Addr::Tls(
+                "cache.example.com:11211".to_string(),
+                "cache.example.com".to_string()
+            ))
+        );
+        assert_eq!(
+            Addr::parse("tcp+tls://[::1]:11212"),
+            Ok(Addr::Tls("[::1]:11212".to_string(), "::1".to_string()))
+        );
+    }
+
+    #[cfg(not(feature = "tls"))]
+    #[tokio::test]
+    async fn test_tls_scheme_requires_feature() {
+        let result = Connection::new("tls://localhost:11211", &ClientConfig::default()).await;
+
+        assert!(
+            matches!(result, Err(Error::Connect(e)) if e.kind() == std::io::ErrorKind::InvalidInput)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_no_scheme_with_ip_address() {
+        assert_eq!(
+            Addr::parse("[::1]:11211"),
+            Ok(Addr::Unknown("[::1]:11211".to_string()))
+        );
+        assert_eq!(
+            Addr::parse("127.0.0.1:11211"),
+            Ok(Addr::Unknown("127.0.0.1:11211".to_string()))
+        );
+    }
+
     #[tokio::test]
     async fn test_invalid_url() {
         assert!(matches!(
@@ -169,4 +511,118 @@ mod tests {
             Error::Connect(_)
         ));
     }
+
+    #[tokio::test]
+    async fn test_read_times_out_without_response() {
+        let config = ClientConfig {
+            read_timeout: Some(Duration::from_millis(20)),
+            ..Default::default()
+        };
+        let mut conn = Connection::new(silent_server().await, &config)
+            .await
+            .unwrap();
+
+        let err = read_response(&mut conn).await.unwrap_err();
+
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+    }
+
+    #[tokio::test]
+    async fn test_deadline_bounds_reads_without_read_timeout() {
+        let mut conn = Connection::new(silent_server().await, &ClientConfig::default())
+            .await
+            .unwrap();
+
+        conn.set_deadline(Some(Instant::now() + Duration::from_millis(20)));
+        let err = read_response(&mut conn).await.unwrap_err();
+
+        assert_eq!(Error::from(err), Error::Timeout);
+    }
+
+    #[tokio::test]
+    async fn test_deadline_applies_before_longer_read_timeout() {
+        let config = ClientConfig {
+            read_timeout: Some(Duration::from_secs(5)),
+            ..Default::default()
+        };
+        let mut conn = Connection::new(silent_server().await, &config)
+            .await
+            .unwrap();
+
+        let start = Instant::now();
+        conn.set_deadline(Some(start + Duration::from_millis(20)));
+        assert!(read_response(&mut conn).await.is_err());
+
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_interleave_families() {
+        let addrs = [
+            "[::1]:1",
+            "[::1]:2",
+            "[::1]:3",
+            "127.0.0.1:1",
+            "127.0.0.1:2",
+        ]
+        .iter()
+        .map(|a| a.parse().unwrap())
+        .collect();
+
+        let ordered: Vec<String> = interleave_families(addrs)
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+
+        assert_eq!(
+            ordered,
+            vec![
+                "[::1]:1",
+                "127.0.0.1:1",
+                "[::1]:2",
+                "127.0.0.1:2",
+                "[::1]:3"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_connects_over_ipv6() {
+        let listener = TcpListener::bind("[::1]:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let dsn = format!("tcp://[::1]:{}", port);
+        assert!(Connection::new(dsn, &ClientConfig::default()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_happy_eyeballs_falls_back_to_next_address() {
+        // Bind and immediately release a port, so that connecting to it is refused.
+        let refused = TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let listener = TcpListener::bind("[::1]:0").await.unwrap();
+
+        let stream = connect_happy_eyeballs(vec![refused, listener.local_addr().unwrap()])
+            .await
+            .unwrap();
+
+        assert_eq!(stream.peer_addr().unwrap(), listener.local_addr().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_happy_eyeballs_reports_last_error() {
+        let refused = TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        assert!(matches!(
+            connect_happy_eyeballs(vec![refused]).await,
+            Err(Error::Connect(e)) if e.kind() == std::io::ErrorKind::ConnectionRefused
+        ));
+    }
 }
@@ -14,6 +14,19 @@ pub enum Error {
     Protocol(Status),
     /// A parsing error surfaced from nom
     ParseError(nom::error::ErrorKind),
+    /// An operation did not complete within its configured timeout.
+    ///
+    /// The connection is left at an unknown position in the response stream, so the client is
+    /// poisoned afterwards.
+    Timeout,
+    /// The client refused an operation because a previous one did not run to completion.
+    ///
+    /// See [`Client::is_poisoned`](crate::Client::is_poisoned).
+    Poisoned,
+    /// The server rejected the credentials used to authenticate the connection.
+    ///
+    /// Contains the error returned by the server.
+    Authentication(String),
 }
 
 impl PartialEq for Error {
@@ -22,6 +35,9 @@ impl PartialEq for Error {
             (Self::Connect(e1), Self::Connect(e2)) => e1.kind() == e2.kind(),
             (Self::Io(e1), Self::Io(e2)) => e1.kind() == e2.kind(),
             (Self::Protocol(s1), Self::Protocol(s2)) => s1 == s2,
+            (Self::Timeout, Self::Timeout) => true,
+            (Self::Poisoned, Self::Poisoned) => true,
+            (Self::Authentication(m1), Self::Authentication(m2)) => m1 == m2,
             _ => false,
         }
     }
@@ -43,13 +59,22 @@ impl fmt::Display for Error {
             Self::Io(e) => write!(f, "io: {}", e),
             Self::Protocol(e) => write!(f, "protocol: {}", e),
             Self::ParseError(e) => write!(f, "parse error: {:?}", e),
+            Self::Timeout => write!(f, "operation timed out"),
+            Self::Poisoned => write!(
+                f,
+                "client is poisoned by a previous operation that did not run to completion"
+            ),
+            Self::Authentication(e) => write!(f, "authentication failed: {}", e),
         }
     }
 }
 
 impl From<std::io::Error> for Error {
     fn from(e: std::io::Error) -> Self {
-        Error::Io(e)
+        match e.kind() {
+            std::io::ErrorKind::TimedOut => Error::Timeout,
+            _ => Error::Io(e),
+        }
     }
 }
 
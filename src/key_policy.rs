@@ -0,0 +1,68 @@
+use std::fmt::Write;
+
+use sha2::{Digest, Sha256};
+
+/// Policy for rewriting keys that memcached would otherwise reject.
+///
+/// Without a policy, keys that exceed the maximum key length of 250 bytes, or that contain
+/// whitespace or control characters, are rejected with
+/// [`ErrorKind::KeyTooLong`](crate::ErrorKind::KeyTooLong) or
+/// [`ErrorKind::InvalidKey`](crate::ErrorKind::InvalidKey).  With a policy set through
+/// [`ClientConfig::key_policy`](crate::ClientConfig::key_policy), such keys are instead replaced by
+/// the prefix followed by the hex-encoded SHA-256 digest of the key, for every command.  Valid keys
+/// are sent unchanged.
+///
+/// The rewrite is deterministic, so clients configured with the same prefix agree on where a key is
+/// stored.  Values returned by the server are reported under the original key.  The prefix should
+/// not be the start of any regular key, so that a hashed key cannot collide with one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyPolicy {
+    /// Prefix of every hashed key.
+    pub prefix: String,
+}
+
+impl Default for KeyPolicy {
+    fn default() -> Self {
+        KeyPolicy {
+            prefix: "sha256:".to_string(),
+        }
+    }
+}
+
+impl KeyPolicy {
+    /// Returns the hashed form of the given key.
+    pub(crate) fn hash_key(&self, key: &[u8]) -> Vec<u8> {
+        let mut hashed = String::with_capacity(self.prefix.len() + 64);
+        hashed.push_str(&self.prefix);
+        for byte in Sha256::digest(key) {
+            write!(hashed, "{:02x}", byte).expect("writing to a string cannot fail");
+        }
+        hashed.into_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeyPolicy;
+
+    #[test]
+    fn test_hash_key() {
+        let policy = KeyPolicy::default();
+
+        assert_eq!(
+            policy.hash_key(b"foo"),
+            b"sha256:2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_hash_key_uses_prefix() {
+        let policy = KeyPolicy {
+            prefix: "h/".to_string(),
+        };
+
+        let hashed = policy.hash_key(&[b'a'; 300]);
+        assert!(hashed.starts_with(b"h/"));
+        assert_eq!(hashed.len(), 2 + 64);
+    }
+}
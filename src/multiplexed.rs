@@ -0,0 +1,632 @@
+use std::borrow::Cow;
+use std::io;
+use std::time::Duration;
+
+use bytes::BytesMut;
+use fxhash::FxHashMap;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, ReadHalf, WriteHalf};
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::connection::Connection;
+use crate::parser::{
+    parse_ascii_response, parse_meta_get_response, ErrorKind, MetaResponse, Response, Status,
+};
+use crate::{
+    has_binary_key_flag, prepare_key, retrieved_meta_value, retrieved_value, retrieved_values,
+    AsMemcachedValue, Client, ClientConfig, Error, KeyPolicy, MetaValue, Value,
+};
+
+// Maximum number of requests queued for writing, and of requests awaiting their response, before
+// callers are made to wait.
+const QUEUE_DEPTH: usize = 1024;
+
+// The protocol a command belongs to, which determines how its response is parsed.
+#[derive(Clone, Copy, Debug)]
+enum Protocol {
+    Ascii,
+    Meta,
+}
+
+enum Frame {
+    Ascii(Response),
+    Meta(MetaResponse),
+}
+
+struct Pending {
+    protocol: Protocol,
+    reply: oneshot::Sender<Result<Frame, Error>>,
+}
+
+struct Request {
+    command: Vec<u8>,
+    pending: Pending,
+}
+
+/// Cloneable handle to a single connection, shared by many tasks.
+///
+/// Unlike [`Client`], every method takes `&self`, and the handle can be cloned and sent to other
+/// tasks.  Requests from all clones are pipelined onto the same connection by a background writer
+/// task, and a background reader task hands each response back to its caller in the order the
+/// requests were written.
+///
+/// Because responses are always read in full by the reader task, a caller that is cancelled or
+/// times out while waiting does not disturb any other request.  If the connection itself fails,
+/// pending and subsequent operations fail, and a new handle must be created.
+///
+/// The background tasks are spawned on the current Tokio runtime, and stop once every clone of the
+/// handle has been dropped.
+#[derive(Clone, Debug)]
+pub struct MultiplexedClient {
+    requests: mpsc::Sender<Request>,
+    request_timeout: Option<Duration>,
+    key_policy: Option<KeyPolicy>,
+}
+
+impl MultiplexedClient {
+    /// Creates a new [`MultiplexedClient`] based on the given data source string.
+    ///
+    /// The DSN follows the same format as [`Client::new`].
+    pub async fn new<S: AsRef<str>>(dsn: S) -> Result<MultiplexedClient, Error> {
+        Self::with_config(dsn, ClientConfig::default()).await
+    }
+
+    /// Creates a new [`MultiplexedClient`] based on the given data source string and
+    /// configuration.
+    ///
+    /// [`ClientConfig::request_timeout`] bounds how long each operation waits for its response,
+    /// including any time spent queued behind other requests, and [`ClientConfig::key_policy`]
+    /// applies to every command.  [`ClientConfig::reconnect`] does not apply.
+    pub async fn with_config<S: AsRef<str>>(
+        dsn: S,
+        config: ClientConfig,
+    ) -> Result<MultiplexedClient, Error> {
+        let conn = Connection::new(dsn.as_ref(), &config).await?;
+        let (reader, writer) = tokio::io::split(conn);
+
+        let (requests_tx, requests_rx) = mpsc::channel(QUEUE_DEPTH);
+        let (pending_tx, pending_rx) = mpsc::channel(QUEUE_DEPTH);
+        tokio::spawn(write_requests(writer, requests_rx, pending_tx));
+        tokio::spawn(read_responses(reader, pending_rx));
+
+        Ok(MultiplexedClient {
+            requests: requests_tx,
+            request_timeout: config.request_timeout,
+            key_policy: config.key_policy,
+        })
+    }
+
+    /// Whether the connection has failed, in which case every operation fails.
+    pub fn is_closed(&self) -> bool {
+        self.requests.is_closed()
+    }
+
+    /// Gets the given key.
+    ///
+    /// If the key is found, `Some(Value)` is returned, describing the metadata and data of the key.
+    ///
+    /// Otherwise, `None` is returned.
+    pub async fn get<K: AsRef<[u8]>>(&self, key: K) -> Result<Option<Value>, Error> {
+        let kr = self.prepare_key(key.as_ref(), false)?;
+
+        let response = self
+            .ascii_request([b"get ", kr.as_ref(), b"\r\n"].concat())
+            .await?;
+
+        // The value is returned under the key that was sent, which may have been hashed.
+        let mut value = retrieved_value(response)?;
+        if let (Cow::Owned(_), Some(value)) = (kr, &mut value) {
+            value.key = key.as_ref().to_vec();
+        }
+
+        Ok(value)
+    }
+
+    /// Gets multiple keys.
+    ///
+    /// If any of the keys are found, a vector of [`Value`] will be returned.
+    ///
+    /// Otherwise, [`Error`] is returned.
+    pub async fn get_multi<I, K>(&self, keys: I) -> Result<Vec<Value>, Error>
+    where
+        I: IntoIterator<Item = K>,
+        K: AsRef<[u8]>,
+    {
+        // Values are returned under the key that was sent, so hashed keys are mapped back to the
+        // caller's keys.
+        let mut hashed_keys = FxHashMap::default();
+
+        let mut command = b"get".to_vec();
+        for key in keys {
+            let Ok(kr) = self.prepare_key(key.as_ref(), false) else {
+                continue;
+            };
+            command.push(b' ');
+            command.extend_from_slice(&kr);
+            if let Cow::Owned(kr) = kr {
+                hashed_keys.insert(kr, key.as_ref().to_vec());
+            }
+        }
+
+        // A command without any keys would be rejected by the server.
+        if command.len() == b"get".len() {
+            return Ok(Vec::new());
+        }
+        command.extend_from_slice(b"\r\n");
+
+        let response = self.ascii_request(command).await?;
+
+        let mut values = retrieved_values(response)?;
+        for value in &mut values {
+            if let Some(key) = hashed_keys.get(&value.key) {
+                value.key = key.clone();
+            }
+        }
+
+        Ok(values)
+    }
+
+    /// Sets the given key.
+    ///
+    /// If `ttl` or `flags` are not specified, they will default to 0.  If the value is set
+    /// successfully, `()` is returned, otherwise [`Error`] is returned.
+    pub async fn set<K, V>(
+        &self,
+        key: K,
+        value: V,
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue,
+    {
+        self.storage_command(b"set", key, value, ttl, flags).await
+    }
+
+    /// Adds the given key if it doesn't already exist.
+    ///
+    /// If `ttl` or `flags` are not specified, they will default to 0.  If the value is added
+    /// successfully, `()` is returned, otherwise [`Error`] is returned.
+    pub async fn add<K, V>(
+        &self,
+        key: K,
+        value: V,
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue,
+    {
+        self.storage_command(b"add", key, value, ttl, flags).await
+    }
+
+    /// Replaces the value of the given key if it already exists.
+    ///
+    /// If `ttl` or `flags` are not specified, they will default to 0.  If the value is replaced
+    /// successfully, `()` is returned, otherwise [`Error`] is returned.
+    pub async fn replace<K, V>(
+        &self,
+        key: K,
+        value: V,
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue,
+    {
+        self.storage_command(b"replace", key, value, ttl, flags)
+            .await
+    }
+
+    /// Deletes the given key.
+    ///
+    /// If the key is deleted, `()` is returned, otherwise [`Error`] is returned.
+    pub async fn delete<K: AsRef<[u8]>>(&self, key: K) -> Result<(), Error> {
+        let kr = self.prepare_key(key.as_ref(), false)?;
+
+        match self
+            .ascii_request([b"delete ", kr.as_ref(), b"\r\n"].concat())
+            .await?
+        {
+            Response::Status(Status::Deleted) => Ok(()),
+            Response::Status(s) => Err(s.into()),
+            _ => Err(Status::Error(ErrorKind::Protocol(None)).into()),
+        }
+    }
+
+    /// Increments the given key by the specified amount, returning the new value.
+    pub async fn increment<K: AsRef<[u8]>>(&self, key: K, amount: u64) -> Result<u64, Error> {
+        self.arithmetic_command(b"incr ", key, amount).await
+    }
+
+    /// Decrements the given key by the specified amount, returning the new value.
+    ///
+    /// The value will not go below 0.
+    pub async fn decrement<K: AsRef<[u8]>>(&self, key: K, amount: u64) -> Result<u64, Error> {
+        self.arithmetic_command(b"decr ", key, amount).await
+    }
+
+    /// Updates the TTL of the given key without fetching its value.
+    ///
+    /// If the key is found, `()` is returned, otherwise [`Error`] is returned.
+    pub async fn touch<K: AsRef<[u8]>>(&self, key: K, ttl: i64) -> Result<(), Error> {
+        let kr = self.prepare_key(key.as_ref(), false)?;
+
+        let command = [
+            b"touch ",
+            kr.as_ref(),
+            b" ",
+            ttl.to_string().as_bytes(),
+            b"\r\n",
+        ]
+        .concat();
+        match self.ascii_request(command).await? {
+            Response::Status(Status::Touched) => Ok(()),
+            Response::Status(s) => Err(s.into()),
+            _ => Err(Status::Error(ErrorKind::Protocol(None)).into()),
+        }
+    }
+
+    /// Gets the given key with additional metadata.
+    ///
+    /// If the key is found, `Some(MetaValue)` is returned, describing the metadata and data of the
+    /// key, as requested by the meta flags.  Quiet mode is not supported, as every command must
+    /// receive a response.
+    ///
+    /// Otherwise, `None` is returned.
+    pub async fn meta_get<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        opaque: Option<&[u8]>,
+        meta_flags: Option<&[&str]>,
+    ) -> Result<Option<MetaValue>, Error> {
+        let kr = self.prepare_key(key.as_ref(), has_binary_key_flag(meta_flags))?;
+
+        let mut command = [b"mg ", kr.as_ref()].concat();
+        if let Some(opaque) = opaque {
+            Client::validate_opaque_length(opaque)?;
+            command.extend_from_slice(b" O");
+            command.extend_from_slice(opaque);
+        }
+        for flag in meta_flags.unwrap_or_default() {
+            // Ignore q flag, and prefer explicit opaque param over O meta flag
+            if flag.starts_with('q') || (flag.starts_with('O') && opaque.is_some()) {
+                continue;
+            }
+            command.push(b' ');
+            command.extend_from_slice(flag.as_bytes());
+        }
+        command.extend_from_slice(b"\r\n");
+
+        let response = self.meta_request(command).await?;
+
+        // Any key is returned as it was sent, which may have been hashed or encoded.
+        let mut value = retrieved_meta_value(response)?;
+        if let Some(value) = value.as_mut().filter(|value| value.key.is_some()) {
+            value.key = Some(key.as_ref().to_vec());
+        }
+
+        Ok(value)
+    }
+
+    async fn storage_command<K, V>(
+        &self,
+        command: &[u8],
+        key: K,
+        value: V,
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue,
+    {
+        let kr = self.prepare_key(key.as_ref(), false)?;
+        let vr = value.as_bytes();
+
+        let header = format!(
+            " {} {} {}\r\n",
+            flags.unwrap_or(0),
+            ttl.unwrap_or(0),
+            vr.len()
+        );
+        let command = [command, b" ", &kr, header.as_bytes(), vr.as_ref(), b"\r\n"].concat();
+
+        match self.ascii_request(command).await? {
+            Response::Status(Status::Stored) => Ok(()),
+            Response::Status(s) => Err(s.into()),
+            _ => Err(Status::Error(ErrorKind::Protocol(None)).into()),
+        }
+    }
+
+    async fn arithmetic_command<K: AsRef<[u8]>>(
+        &self,
+        command: &[u8],
+        key: K,
+        amount: u64,
+    ) -> Result<u64, Error> {
+        let kr = self.prepare_key(key.as_ref(), false)?;
+
+        let command = [command, &kr, b" ", amount.to_string().as_bytes(), b"\r\n"].concat();
+        match self.ascii_request(command).await? {
+            Response::Status(s) => Err(s.into()),
+            Response::IncrDecr(amount) => Ok(amount),
+            _ => Err(Status::Error(ErrorKind::Protocol(None)).into()),
+        }
+    }
+
+    // Returns the key to send to the server for the given key, applying the key policy if there
+    // is one.
+    pub(crate) fn prepare_key<'k>(
+        &self,
+        kr: &'k [u8],
+        binary: bool,
+    ) -> Result<Cow<'k, [u8]>, Error> {
+        prepare_key(kr, binary, self.key_policy.as_ref())
+    }
+
+    async fn ascii_request(&self, command: Vec<u8>) -> Result<Response, Error> {
+        match self.request(command, Protocol::Ascii).await? {
+            Frame::Ascii(response) => Ok(response),
+            Frame::Meta(_) => Err(Status::Error(ErrorKind::Protocol(None)).into()),
+        }
+    }
+
+    async fn meta_request(&self, command: Vec<u8>) -> Result<MetaResponse, Error> {
+        match self.request(command, Protocol::Meta).await? {
+            Frame::Meta(response) => Ok(response),
+            Frame::Ascii(_) => Err(Status::Error(ErrorKind::Protocol(None)).into()),
+        }
+    }
+
+    // Queues a command for writing and waits for its response.
+    async fn request(&self, command: Vec<u8>, protocol: Protocol) -> Result<Frame, Error> {
+        let (reply, response) = oneshot::channel();
+        let pending = Pending { protocol, reply };
+
+        let exchange = async {
+            self.requests
+                .send(Request { command, pending })
+                .await
+                .map_err(|_| closed())?;
+            response.await.map_err(|_| closed())?
+        };
+
+        match self.request_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, exchange)
+                .await
+                .map_err(|_| Error::Timeout)?,
+            None => exchange.await,
+        }
+    }
+}
+
+fn closed() -> Error {
+    Error::Io(io::Error::new(
+        io::ErrorKind::NotConnected,
+        "multiplexed connection is closed",
+    ))
+}
+
+// Writes queued commands in batches, flushing whenever the queue runs empty or the reader falls
+// behind, and passes the reply channel of each written command on to the reader task.
+async fn write_requests(
+    mut writer: WriteHalf<Connection>,
+    mut requests: mpsc::Receiver<Request>,
+    pending: mpsc::Sender<Pending>,
+) {
+    while let Some(mut request) = requests.recv().await {
+        loop {
+            if let Err(e) = writer.write_all(&request.command).await {
+                let _ = request.pending.reply.send(Err(e.into()));
+                return;
+            }
+
+            match pending.try_send(request.pending) {
+                Ok(()) => {}
+                // The reader only makes room once it has read the responses to commands that were
+                // flushed, so the written commands are flushed before waiting for room.
+                Err(TrySendError::Full(next)) => {
+                    if writer.flush().await.is_err() || pending.send(next).await.is_err() {
+                        return;
+                    }
+                }
+                Err(TrySendError::Closed(_)) => return,
+            }
+
+            match requests.try_recv() {
+                Ok(next) => request = next,
+                Err(_) => break,
+            }
+        }
+
+        if writer.flush().await.is_err() {
+            return;
+        }
+    }
+}
+
+// Reads responses in the order their commands were written, and hands each one to its caller.
+// Callers that have stopped waiting are skipped, after their response has been read in full.
+async fn read_responses(mut reader: ReadHalf<Connection>, mut pending: mpsc::Receiver<Pending>) {
+    let mut buf = BytesMut::new();
+
+    while let Some(Pending { protocol, reply }) = pending.recv().await {
+        let response = read_response(&mut reader, &mut buf, protocol).await;
+        let failed = response.is_err();
+        let _ = reply.send(response);
+
+        if failed {
+            return;
+        }
+    }
+}
+
+async fn read_response(
+    reader: &mut ReadHalf<Connection>,
+    buf: &mut BytesMut,
+    protocol: Protocol,
+) -> Result<Frame, Error> {
+    loop {
+        let parsed = match protocol {
+            Protocol::Ascii => {
+                parse_ascii_response(buf).map(|r| r.map(|(n, r)| (n, Frame::Ascii(r))))
+            }
+            Protocol::Meta => {
+                parse_meta_get_response(buf).map(|r| r.map(|(n, r)| (n, Frame::Meta(r))))
+            }
+        };
+
+        match parsed {
+            Ok(Some((n, response))) => {
+                let _ = buf.split_to(n);
+                return Ok(response);
+            }
+            Ok(None) => {}
+            Err(kind) => return Err(Status::Error(kind).into()),
+        }
+
+        buf.reserve(1024);
+        if reader.read_buf(buf).await? == 0 {
+            return Err(Error::Io(io::ErrorKind::UnexpectedEof.into()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MultiplexedClient, QUEUE_DEPTH};
+    use crate::{ClientConfig, Error};
+
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    // Accepts a single connection and answers each `get` with the requested key's name as its
+    // value.  The response to the key `slow` is delayed, holding up every response after it.
+    async fn echo_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut stream = BufReader::new(stream);
+
+            loop {
+                let mut line = String::new();
+                if stream.read_line(&mut line).await.unwrap_or(0) == 0 {
+                    return;
+                }
+
+                let key = line.trim_end().trim_start_matches("get ").to_string();
+                if key == "slow" {
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                }
+
+                let response = format!("VALUE {} 0 {}\r\n{}\r\nEND\r\n", key, key.len(), key);
+                stream
+                    .get_mut()
+                    .write_all(response.as_bytes())
+                    .await
+                    .unwrap();
+            }
+        });
+
+        format!("tcp://{}", addr)
+    }
+
+    #[test]
+    fn test_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync + Clone + 'static>() {}
+        assert_send_sync::<MultiplexedClient>();
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_receive_their_own_responses() {
+        let client = MultiplexedClient::new(echo_server().await).await.unwrap();
+
+        let tasks: Vec<_> = (0..100)
+            .map(|i| {
+                let client = client.clone();
+                tokio::spawn(async move {
+                    let key = format!("key-{}", i);
+                    let value = client.get(&key).await.unwrap().unwrap();
+                    assert_eq!(value.data, Some(key.into_bytes()));
+                })
+            })
+            .collect();
+
+        for task in tasks {
+            task.await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_more_concurrent_requests_than_queue_depth() {
+        let client = MultiplexedClient::new(echo_server().await).await.unwrap();
+
+        // Short keys fit more commands into the write buffer than there is room for pending
+        // responses.
+        let tasks: Vec<_> = (0..QUEUE_DEPTH * 3)
+            .map(|i| {
+                let client = client.clone();
+                tokio::spawn(async move {
+                    let key = format!("{}", i % 10);
+                    let value = client.get(&key).await.unwrap().unwrap();
+                    assert_eq!(value.data, Some(key.into_bytes()));
+                })
+            })
+            .collect();
+
+        let all = futures::future::join_all(tasks);
+        for result in tokio::time::timeout(Duration::from_secs(5), all)
+            .await
+            .expect("requests deadlocked")
+        {
+            result.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timed_out_request_does_not_disturb_others() {
+        let config = ClientConfig {
+            request_timeout: Some(Duration::from_millis(50)),
+            ..Default::default()
+        };
+        let client = MultiplexedClient::with_config(echo_server().await, config)
+            .await
+            .unwrap();
+
+        assert_eq!(client.get("slow").await, Err(Error::Timeout));
+
+        // The response to the timed out request arrives in the meantime, and must be discarded.
+        tokio::time::sleep(Duration::from_millis(250)).await;
+
+        let value = client.get("fast").await.unwrap().unwrap();
+        assert_eq!(value.data, Some(b"fast".to_vec()));
+    }
+
+    #[tokio::test]
+    async fn test_operations_fail_once_connection_is_closed() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            drop(stream);
+        });
+
+        let client = MultiplexedClient::new(format!("tcp://{}", addr))
+            .await
+            .unwrap();
+
+        assert!(client.get("foo").await.is_err());
+        assert!(matches!(client.get("foo").await, Err(Error::Io(_))));
+
+        // Give the background tasks a moment to shut down.
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(client.is_closed());
+    }
+}
@@ -0,0 +1,848 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use fxhash::FxHashMap;
+
+use crate::{
+    AsMemcachedValue, AsciiProtocol, CasOutcome, Client, Error, ErrorKind, MetaArithmeticFlags,
+    MetaDebugInfo, MetaDeleteFlags, MetaGetFlags, MetaProtocol, MetaSetFlags, MetaSetItem,
+    MetaSetOutcome, MetaValue, Status, Value,
+};
+
+/// A view over a [`Client`] that keeps every key within a namespace.
+///
+/// Every key sent through the view is prefixed with the namespace, and the prefix is stripped from
+/// the keys of returned values, so that [`Value::key`], [`MetaValue::key`] and
+/// [`MetaDebugInfo::key`] hold the keys as given by the caller.
+///
+/// A namespace created with [`Namespace::with_generation`] also has a generation, a counter stored
+/// in memcached under the prefix itself, and keys are sent as `<prefix><generation>:<key>`.
+/// [`Namespace::invalidate`] bumps the generation, which invalidates every key in the namespace at
+/// once: the old items are no longer reachable, and are eventually evicted by memcached.
+///
+/// The generation is read when the view is created, so views created before another client
+/// invalidates the namespace keep using the old generation until they are recreated.
+pub struct Namespace<'c> {
+    client: &'c mut Client,
+    prefix: Vec<u8>,
+    generation: Option<u64>,
+    key_prefix: Vec<u8>,
+}
+
+impl<'c> Namespace<'c> {
+    /// Creates a view over the given client that prefixes every key with `prefix`.
+    pub fn new<P: AsRef<[u8]>>(client: &'c mut Client, prefix: P) -> Namespace<'c> {
+        let prefix = prefix.as_ref().to_vec();
+
+        Namespace {
+            client,
+            key_prefix: prefix.clone(),
+            prefix,
+            generation: None,
+        }
+    }
+
+    /// Creates a view over the given client that prefixes every key with `prefix` and the current
+    /// generation of the namespace.
+    ///
+    /// If the namespace has no generation yet, or its generation was evicted, a new one is started
+    /// from the current Unix time, so that it does not reuse the generation of existing items.
+    pub async fn with_generation<P: AsRef<[u8]>>(
+        client: &'c mut Client,
+        prefix: P,
+    ) -> Result<Namespace<'c>, Error> {
+        let prefix = prefix.as_ref().to_vec();
+        let generation = load_generation(client, &prefix).await?;
+
+        Ok(Namespace {
+            client,
+            key_prefix: generation_prefix(&prefix, generation),
+            prefix,
+            generation: Some(generation),
+        })
+    }
+
+    /// Returns the prefix of the namespace.
+    pub fn prefix(&self) -> &[u8] {
+        &self.prefix
+    }
+
+    /// Returns the generation of the namespace, if it was created with
+    /// [`Namespace::with_generation`].
+    pub fn generation(&self) -> Option<u64> {
+        self.generation
+    }
+
+    /// Invalidates every key in the namespace by bumping its generation, and returns the new
+    /// generation, which is used by this view from then on.
+    ///
+    /// Fails without sending anything if the namespace was not created with
+    /// [`Namespace::with_generation`].
+    pub async fn invalidate(&mut self) -> Result<u64, Error> {
+        if self.generation.is_none() {
+            return Err(Error::Protocol(Status::Error(ErrorKind::Generic(
+                "Namespace has no generation".to_string(),
+            ))));
+        }
+
+        let generation = match self.client.increment(&self.prefix, 1).await {
+            Ok(generation) => generation,
+            // The generation was evicted, and a new one supersedes it all the same.
+            Err(Error::Protocol(Status::NotFound)) => {
+                load_generation(self.client, &self.prefix).await?
+            }
+            Err(e) => return Err(e),
+        };
+
+        self.generation = Some(generation);
+        self.key_prefix = generation_prefix(&self.prefix, generation);
+        Ok(generation)
+    }
+
+    fn key(&self, key: &[u8]) -> Vec<u8> {
+        [self.key_prefix.as_slice(), key].concat()
+    }
+
+    fn keys<I, K>(&self, keys: I) -> Vec<Vec<u8>>
+    where
+        I: IntoIterator<Item = K>,
+        K: AsRef<[u8]>,
+    {
+        keys.into_iter().map(|key| self.key(key.as_ref())).collect()
+    }
+
+    fn strip(&self, key: &mut Vec<u8>) {
+        if key.starts_with(&self.key_prefix) {
+            key.drain(..self.key_prefix.len());
+        }
+    }
+
+    fn strip_value(&self, mut value: Value) -> Value {
+        self.strip(&mut value.key);
+        value
+    }
+
+    fn strip_values(&self, values: Vec<Value>) -> Vec<Value> {
+        values
+            .into_iter()
+            .map(|value| self.strip_value(value))
+            .collect()
+    }
+
+    fn strip_meta_value(&self, mut value: MetaValue) -> MetaValue {
+        if let Some(key) = value.key.as_mut() {
+            self.strip(key);
+        }
+        value
+    }
+
+    async fn storage_command_multi<'a, K, V>(
+        &mut self,
+        command: &[u8],
+        kv: &'a [(K, V)],
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> Result<FxHashMap<&'a K, Result<(), Error>>, Error>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash + std::fmt::Debug,
+        V: AsMemcachedValue,
+    {
+        let values: Vec<_> = kv.iter().map(|(_, value)| value.as_bytes()).collect();
+        let prefixed: Vec<(Vec<u8>, &[u8])> = kv
+            .iter()
+            .zip(&values)
+            .map(|((key, _), value)| (self.key(key.as_ref()), value.as_ref()))
+            .collect();
+
+        let results = self
+            .client
+            .storage_command_multi(command, &prefixed, ttl, flags)
+            .await?;
+
+        Ok(restore_keys(
+            kv.iter().map(|(key, _)| key),
+            prefixed.iter().map(|(key, _)| key),
+            results,
+        ))
+    }
+}
+
+// Reads the generation of the namespace, starting a new one if there is none.
+async fn load_generation(client: &mut Client, prefix: &[u8]) -> Result<u64, Error> {
+    if let Some(value) = client.get(prefix).await? {
+        return parse_generation(&value);
+    }
+
+    let initial = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(1);
+
+    match client.add(prefix, initial, None, None).await {
+        Ok(()) => Ok(initial),
+        // Another client started a generation first, so it is used instead.
+        Err(Error::Protocol(Status::NotStored)) => match client.get(prefix).await? {
+            Some(value) => parse_generation(&value),
+            None => Err(Error::Protocol(Status::NotFound)),
+        },
+        Err(e) => Err(e),
+    }
+}
+
+fn parse_generation(value: &Value) -> Result<u64, Error> {
+    value
+        .data
+        .as_deref()
+        .and_then(|data| std::str::from_utf8(data).ok())
+        .and_then(|data| data.trim().parse().ok())
+        .ok_or_else(|| {
+            Error::Protocol(Status::Error(ErrorKind::Generic(
+                "Invalid namespace generation".to_string(),
+            )))
+        })
+}
+
+fn generation_prefix(prefix: &[u8], generation: u64) -> Vec<u8> {
+    [prefix, generation.to_string().as_bytes(), b":"].concat()
+}
+
+// Maps results reported under prefixed keys back to the caller's keys, which are given in the same
+// order as the prefixed keys.
+fn restore_keys<'a, 'p, K, T>(
+    keys: impl IntoIterator<Item = &'a K>,
+    prefixed: impl IntoIterator<Item = &'p Vec<u8>>,
+    mut results: FxHashMap<&'p Vec<u8>, T>,
+) -> FxHashMap<&'a K, T>
+where
+    K: Eq + std::hash::Hash + 'a,
+{
+    keys.into_iter()
+        .zip(prefixed)
+        .filter_map(|(key, prefixed)| Some((key, results.remove(prefixed)?)))
+        .collect()
+}
+
+impl AsciiProtocol for Namespace<'_> {
+    async fn get<K: AsRef<[u8]>>(&mut self, key: K) -> Result<Option<Value>, Error> {
+        let key = self.key(key.as_ref());
+        let value = self.client.get(key).await?;
+        Ok(value.map(|value| self.strip_value(value)))
+    }
+
+    async fn get_multi<I, K>(&mut self, keys: I) -> Result<Vec<Value>, Error>
+    where
+        I: IntoIterator<Item = K>,
+        K: AsRef<[u8]>,
+    {
+        let keys = self.keys(keys);
+        let values = self.client.get_multi(keys).await?;
+        Ok(self.strip_values(values))
+    }
+
+    async fn get_many<I, K>(&mut self, keys: I) -> Result<Vec<Value>, Error>
+    where
+        I: IntoIterator<Item = K>,
+        K: AsRef<[u8]>,
+    {
+        self.get_multi(keys).await
+    }
+
+    async fn gets<K: AsRef<[u8]>>(&mut self, key: K) -> Result<Option<Value>, Error> {
+        let key = self.key(key.as_ref());
+        let value = self.client.gets(key).await?;
+        Ok(value.map(|value| self.strip_value(value)))
+    }
+
+    async fn gets_multi<I, K>(&mut self, keys: I) -> Result<Vec<Value>, Error>
+    where
+        I: IntoIterator<Item = K>,
+        K: AsRef<[u8]>,
+    {
+        let keys = self.keys(keys);
+        let values = self.client.gets_multi(keys).await?;
+        Ok(self.strip_values(values))
+    }
+
+    async fn gat<K: AsRef<[u8]>>(&mut self, key: K, ttl: i64) -> Result<Option<Value>, Error> {
+        let key = self.key(key.as_ref());
+        let value = self.client.gat(key, ttl).await?;
+        Ok(value.map(|value| self.strip_value(value)))
+    }
+
+    async fn gat_multi<I, K>(&mut self, keys: I, ttl: i64) -> Result<Vec<Value>, Error>
+    where
+        I: IntoIterator<Item = K>,
+        K: AsRef<[u8]>,
+    {
+        let keys = self.keys(keys);
+        let values = self.client.gat_multi(keys, ttl).await?;
+        Ok(self.strip_values(values))
+    }
+
+    async fn gats<K: AsRef<[u8]>>(&mut self, key: K, ttl: i64) -> Result<Option<Value>, Error> {
+        let key = self.key(key.as_ref());
+        let value = self.client.gats(key, ttl).await?;
+        Ok(value.map(|value| self.strip_value(value)))
+    }
+
+    async fn gats_multi<I, K>(&mut self, keys: I, ttl: i64) -> Result<Vec<Value>, Error>
+    where
+        I: IntoIterator<Item = K>,
+        K: AsRef<[u8]>,
+    {
+        let keys = self.keys(keys);
+        let values = self.client.gats_multi(keys, ttl).await?;
+        Ok(self.strip_values(values))
+    }
+
+    async fn touch<K>(&mut self, key: K, ttl: i64) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        let key = self.key(key.as_ref());
+        self.client.touch(key, ttl).await
+    }
+
+    async fn set<K, V>(
+        &mut self,
+        key: K,
+        value: V,
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue,
+    {
+        let key = self.key(key.as_ref());
+        self.client.set(key, value, ttl, flags).await
+    }
+
+    async fn set_multi<'a, K, V>(
+        &mut self,
+        kv: &'a [(K, V)],
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> Result<FxHashMap<&'a K, Result<(), Error>>, Error>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash + std::fmt::Debug,
+        V: AsMemcachedValue,
+    {
+        self.storage_command_multi(b"set", kv, ttl, flags).await
+    }
+
+    async fn add<K, V>(
+        &mut self,
+        key: K,
+        value: V,
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue,
+    {
+        let key = self.key(key.as_ref());
+        self.client.add(key, value, ttl, flags).await
+    }
+
+    async fn add_multi<'a, K, V>(
+        &mut self,
+        kv: &'a [(K, V)],
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> Result<FxHashMap<&'a K, Result<(), Error>>, Error>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash + std::fmt::Debug,
+        V: AsMemcachedValue,
+    {
+        self.storage_command_multi(b"add", kv, ttl, flags).await
+    }
+
+    async fn replace<K, V>(
+        &mut self,
+        key: K,
+        value: V,
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue,
+    {
+        let key = self.key(key.as_ref());
+        self.client.replace(key, value, ttl, flags).await
+    }
+
+    async fn replace_multi<'a, K, V>(
+        &mut self,
+        kv: &'a [(K, V)],
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> Result<FxHashMap<&'a K, Result<(), Error>>, Error>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash + std::fmt::Debug,
+        V: AsMemcachedValue,
+    {
+        self.storage_command_multi(b"replace", kv, ttl, flags).await
+    }
+
+    async fn append<K, V>(&mut self, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue,
+    {
+        let key = self.key(key.as_ref());
+        self.client.append(key, value).await
+    }
+
+    async fn append_multi<'a, K, V>(
+        &mut self,
+        kv: &'a [(K, V)],
+    ) -> Result<FxHashMap<&'a K, Result<(), Error>>, Error>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash + std::fmt::Debug,
+        V: AsMemcachedValue,
+    {
+        self.storage_command_multi(b"append", kv, None, None).await
+    }
+
+    async fn prepend<K, V>(&mut self, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue,
+    {
+        let key = self.key(key.as_ref());
+        self.client.prepend(key, value).await
+    }
+
+    async fn prepend_multi<'a, K, V>(
+        &mut self,
+        kv: &'a [(K, V)],
+    ) -> Result<FxHashMap<&'a K, Result<(), Error>>, Error>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash + std::fmt::Debug,
+        V: AsMemcachedValue,
+    {
+        self.storage_command_multi(b"prepend", kv, None, None).await
+    }
+
+    async fn cas<K, V>(
+        &mut self,
+        key: K,
+        value: V,
+        cas_unique: u64,
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> Result<CasOutcome, Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue,
+    {
+        let key = self.key(key.as_ref());
+        self.client.cas(key, value, cas_unique, ttl, flags).await
+    }
+
+    async fn delete_multi_no_reply<K>(&mut self, keys: &[K]) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        let keys = self.keys(keys);
+        self.client.delete_multi_no_reply(&keys).await
+    }
+
+    async fn delete_no_reply<K>(&mut self, key: K) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        let key = self.key(key.as_ref());
+        self.client.delete_no_reply(key).await
+    }
+
+    async fn delete<K>(&mut self, key: K) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        let key = self.key(key.as_ref());
+        self.client.delete(key).await
+    }
+
+    async fn increment<K>(&mut self, key: K, amount: u64) -> Result<u64, Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        let key = self.key(key.as_ref());
+        self.client.increment(key, amount).await
+    }
+
+    async fn increment_no_reply<K>(&mut self, key: K, amount: u64) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        let key = self.key(key.as_ref());
+        self.client.increment_no_reply(key, amount).await
+    }
+
+    async fn decrement<K>(&mut self, key: K, amount: u64) -> Result<u64, Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        let key = self.key(key.as_ref());
+        self.client.decrement(key, amount).await
+    }
+
+    async fn decrement_no_reply<K>(&mut self, key: K, amount: u64) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        let key = self.key(key.as_ref());
+        self.client.decrement_no_reply(key, amount).await
+    }
+}
+
+impl MetaProtocol for Namespace<'_> {
+    async fn meta_get<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        meta_flags: Option<&[&str]>,
+    ) -> Result<Option<MetaValue>, Error> {
+        let key = self.key(key.as_ref());
+        let value = self
+            .client
+            .meta_get(key, is_quiet, opaque, meta_flags)
+            .await?;
+        Ok(value.map(|value| self.strip_meta_value(value)))
+    }
+
+    async fn meta_get_with<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        flags: &MetaGetFlags,
+    ) -> Result<Option<MetaValue>, Error> {
+        let key = self.key(key.as_ref());
+        let value = self
+            .client
+            .meta_get_with(key, is_quiet, opaque, flags)
+            .await?;
+        Ok(value.map(|value| self.strip_meta_value(value)))
+    }
+
+    async fn meta_get_multi<'a, K>(
+        &mut self,
+        keys: &'a [K],
+        meta_flags: Option<&[&str]>,
+    ) -> Result<FxHashMap<&'a K, MetaValue>, Error>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash,
+    {
+        let prefixed = self.keys(keys);
+        let values = self.client.meta_get_multi(&prefixed, meta_flags).await?;
+
+        let mut values = restore_keys(keys, &prefixed, values);
+        for value in values.values_mut() {
+            *value = self.strip_meta_value(std::mem::take(value));
+        }
+        Ok(values)
+    }
+
+    async fn meta_set<K, V>(
+        &mut self,
+        key: K,
+        value: V,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        meta_flags: Option<&[&str]>,
+    ) -> Result<Option<MetaValue>, Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue,
+    {
+        let key = self.key(key.as_ref());
+        let value = self
+            .client
+            .meta_set(key, value, is_quiet, opaque, meta_flags)
+            .await?;
+        Ok(value.map(|value| self.strip_meta_value(value)))
+    }
+
+    async fn meta_set_with<K, V>(
+        &mut self,
+        key: K,
+        value: V,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        flags: &MetaSetFlags,
+    ) -> Result<MetaSetOutcome, Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue,
+    {
+        let key = self.key(key.as_ref());
+        let outcome = self
+            .client
+            .meta_set_with(key, value, is_quiet, opaque, flags)
+            .await?;
+
+        Ok(match outcome {
+            MetaSetOutcome::Stored(value) => {
+                MetaSetOutcome::Stored(value.map(|value| self.strip_meta_value(value)))
+            }
+            outcome => outcome,
+        })
+    }
+
+    async fn meta_set_multi<'a, K, V>(
+        &mut self,
+        items: &'a [MetaSetItem<K, V>],
+        meta_flags: Option<&[&str]>,
+    ) -> Result<FxHashMap<&'a K, Result<(), Error>>, Error>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash,
+        V: AsMemcachedValue,
+    {
+        let values: Vec<_> = items
+            .iter()
+            .map(|(_, value, ..)| value.as_bytes())
+            .collect();
+        let prefixed: Vec<MetaSetItem<Vec<u8>, &[u8]>> = items
+            .iter()
+            .zip(&values)
+            .map(|((key, _, ttl, flags), value)| {
+                (self.key(key.as_ref()), value.as_ref(), *ttl, *flags)
+            })
+            .collect();
+
+        let results = self.client.meta_set_multi(&prefixed, meta_flags).await?;
+
+        Ok(restore_keys(
+            items.iter().map(|(key, ..)| key),
+            prefixed.iter().map(|(key, ..)| key),
+            results,
+        ))
+    }
+
+    async fn meta_delete<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        meta_flags: Option<&[&str]>,
+    ) -> Result<Option<MetaValue>, Error> {
+        let key = self.key(key.as_ref());
+        let value = self
+            .client
+            .meta_delete(key, is_quiet, opaque, meta_flags)
+            .await?;
+        Ok(value.map(|value| self.strip_meta_value(value)))
+    }
+
+    async fn meta_delete_with<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        flags: &MetaDeleteFlags,
+    ) -> Result<Option<MetaValue>, Error> {
+        let key = self.key(key.as_ref());
+        let value = self
+            .client
+            .meta_delete_with(key, is_quiet, opaque, flags)
+            .await?;
+        Ok(value.map(|value| self.strip_meta_value(value)))
+    }
+
+    async fn meta_delete_multi<'a, K>(
+        &mut self,
+        keys: &'a [K],
+        meta_flags: Option<&[&str]>,
+    ) -> Result<FxHashMap<&'a K, Result<(), Error>>, Error>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash,
+    {
+        let prefixed = self.keys(keys);
+        let results = self.client.meta_delete_multi(&prefixed, meta_flags).await?;
+        Ok(restore_keys(keys, &prefixed, results))
+    }
+
+    async fn meta_increment<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        delta: Option<u64>,
+        meta_flags: Option<&[&str]>,
+    ) -> Result<Option<MetaValue>, Error> {
+        let key = self.key(key.as_ref());
+        let value = self
+            .client
+            .meta_increment(key, is_quiet, opaque, delta, meta_flags)
+            .await?;
+        Ok(value.map(|value| self.strip_meta_value(value)))
+    }
+
+    async fn meta_increment_with<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        delta: Option<u64>,
+        flags: &MetaArithmeticFlags,
+    ) -> Result<Option<MetaValue>, Error> {
+        let key = self.key(key.as_ref());
+        let value = self
+            .client
+            .meta_increment_with(key, is_quiet, opaque, delta, flags)
+            .await?;
+        Ok(value.map(|value| self.strip_meta_value(value)))
+    }
+
+    async fn meta_decrement<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        delta: Option<u64>,
+        meta_flags: Option<&[&str]>,
+    ) -> Result<Option<MetaValue>, Error> {
+        let key = self.key(key.as_ref());
+        let value = self
+            .client
+            .meta_decrement(key, is_quiet, opaque, delta, meta_flags)
+            .await?;
+        Ok(value.map(|value| self.strip_meta_value(value)))
+    }
+
+    async fn meta_decrement_with<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        delta: Option<u64>,
+        flags: &MetaArithmeticFlags,
+    ) -> Result<Option<MetaValue>, Error> {
+        let key = self.key(key.as_ref());
+        let value = self
+            .client
+            .meta_decrement_with(key, is_quiet, opaque, delta, flags)
+            .await?;
+        Ok(value.map(|value| self.strip_meta_value(value)))
+    }
+
+    async fn meta_noop(&mut self) -> Result<(), Error> {
+        self.client.meta_noop().await
+    }
+
+    async fn meta_debug<K: AsRef<[u8]>>(&mut self, key: K) -> Result<Option<MetaDebugInfo>, Error> {
+        let key = self.key(key.as_ref());
+        let info = self.client.meta_debug(key).await?;
+        Ok(info.map(|mut info| {
+            self.strip(&mut info.key);
+            info
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Namespace;
+    use crate::{AsciiProtocol, Client, Error, ErrorKind, Status};
+
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    // Accepts a single connection, records every line it receives, and replies to each line with
+    // the next scripted response.
+    async fn recording_server(
+        responses: &'static [&'static [u8]],
+    ) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let recorded = lines.clone();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut stream = BufReader::new(stream);
+
+            for response in responses {
+                let mut line = String::new();
+                if stream.read_line(&mut line).await.unwrap() == 0 {
+                    return;
+                }
+                recorded.lock().unwrap().push(line.trim_end().to_string());
+                stream.get_mut().write_all(response).await.unwrap();
+            }
+
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        });
+
+        (format!("tcp://{}", addr), lines)
+    }
+
+    #[tokio::test]
+    async fn test_keys_are_prefixed_and_stripped() {
+        let (dsn, lines) =
+            recording_server(&[b"", b"STORED\r\n", b"VALUE users:foo 0 3\r\nbar\r\nEND\r\n"]).await;
+        let mut client = Client::new(dsn).await.unwrap();
+        let mut namespace = Namespace::new(&mut client, "users:");
+
+        namespace.set("foo", "bar", None, None).await.unwrap();
+        let value = namespace.get("foo").await.unwrap().unwrap();
+
+        assert_eq!(value.key, b"foo".to_vec());
+        assert_eq!(value.data, Some(b"bar".to_vec()));
+        assert_eq!(
+            *lines.lock().unwrap(),
+            vec!["set users:foo 0 0 3", "bar", "get users:foo"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_bumps_generation() {
+        let (dsn, lines) = recording_server(&[
+            b"VALUE users: 0 1\r\n7\r\nEND\r\n",
+            b"END\r\n",
+            b"8\r\n",
+            b"END\r\n",
+        ])
+        .await;
+        let mut client = Client::new(dsn).await.unwrap();
+        let mut namespace = Namespace::with_generation(&mut client, "users:")
+            .await
+            .unwrap();
+
+        assert_eq!(namespace.generation(), Some(7));
+        assert_eq!(namespace.get("foo").await, Ok(None));
+        assert_eq!(namespace.invalidate().await, Ok(8));
+        assert_eq!(namespace.get("foo").await, Ok(None));
+        assert_eq!(
+            *lines.lock().unwrap(),
+            vec![
+                "get users:",
+                "get users:7:foo",
+                "incr users: 1",
+                "get users:8:foo"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_requires_generation() {
+        let (dsn, lines) = recording_server(&[]).await;
+        let mut client = Client::new(dsn).await.unwrap();
+        let mut namespace = Namespace::new(&mut client, "users:");
+
+        assert_eq!(
+            namespace.invalidate().await,
+            Err(Error::Protocol(Status::Error(ErrorKind::Generic(
+                "Namespace has no generation".to_string()
+            ))))
+        );
+        assert!(lines.lock().unwrap().is_empty());
+    }
+}
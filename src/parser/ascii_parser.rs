@@ -220,6 +220,15 @@ pub fn parse_ascii_stats_response(buf: &[u8]) -> Result<Option<(usize, StatsResp
     }
 }
 
+// Parses a single line of a response, such as the reply to `version` or `flush_all`, including its
+// line ending.
+pub fn parse_ascii_line(buf: &[u8]) -> Result<Option<(usize, String)>, ErrorKind> {
+    Ok(buf.iter().position(|b| *b == b'\n').map(|i| {
+        let n = i + 1;
+        (n, String::from_utf8_lossy(&buf[..n]).into_owned())
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
@@ -1,3 +1,4 @@
+use base64::prelude::{Engine as _, BASE64_STANDARD};
 use nom::{
     branch::alt,
     bytes::streaming::{tag, take, take_while1},
@@ -11,7 +12,10 @@ use nom::{
 
 use std::num::NonZero;
 
-use super::{parse_u32, ErrorKind, MetaResponse, MetaValue, Status};
+use super::{
+    is_key_char, parse_bool, parse_i64, parse_u32, parse_u64, ErrorKind, MetaDebugInfo,
+    MetaResponse, MetaValue, Status,
+};
 use crate::Error;
 
 pub fn parse_meta_get_status(buf: &[u8]) -> IResult<&[u8], MetaResponse> {
@@ -119,6 +123,62 @@ pub fn parse_meta_arithmetic_response(
     }
 }
 
+pub fn parse_meta_debug_response(
+    buf: &[u8],
+) -> Result<Option<(usize, Option<MetaDebugInfo>)>, ErrorKind> {
+    let total_bytes = buf.len();
+    let result = alt((
+        value(None, tag(b"EN\r\n")),
+        map(parse_meta_debug_value, Some),
+    ))(buf);
+
+    match result {
+        Ok((remaining_bytes, response)) => {
+            let read_bytes = total_bytes - remaining_bytes.len();
+            Ok(Some((read_bytes, response)))
+        }
+        Err(nom::Err::Incomplete(_)) => Ok(None),
+        Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => {
+            Err(ErrorKind::Protocol(Some(e.code.description().to_string())))
+        }
+    }
+}
+
+// example meta_debug response from memcached server:
+// ME meta-debug-test-key exp=-1 la=3 cas=2 fetch=no cls=1 size=63
+fn parse_meta_debug_value(buf: &[u8]) -> IResult<&[u8], MetaDebugInfo> {
+    let (buf, (_, key, _, exp, _, la, _, cas, _, fetch, _, cls, _, size, _)) = tuple((
+        tag("ME "),
+        take_while1(is_key_char),
+        tag(" exp="),
+        parse_i64,
+        tag(" la="),
+        parse_u64,
+        tag(" cas="),
+        parse_u64,
+        tag(" fetch="),
+        parse_bool,
+        tag(" cls="),
+        parse_u32,
+        tag(" size="),
+        parse_u32,
+        crlf,
+    ))(buf)?;
+
+    Ok((
+        buf,
+        MetaDebugInfo {
+            key: key.to_vec(),
+            expiration: exp,
+            last_accessed: la,
+            cas,
+            fetched: fetch,
+            class_id: cls,
+            size,
+        },
+    ))
+}
+
 // example meta_get command sent to memcached server:
 // mg meta-get-test-key v h l t k
 // so it has flags of v h l t k
@@ -157,7 +217,8 @@ fn parse_meta_get_data_value(buf: &[u8]) -> IResult<&[u8], MetaResponse> {
             let (input, mut data) = if size > 0 {
                 take_until_size(input, size)? // parses the data from the input
             } else {
-                (input, None) // tombstoned key, no data block
+                let (input, _) = crlf(input)?; // tombstoned key, consume the empty data block
+                (input, None)
             };
 
             // trim the data block of any trailing whitespace
@@ -457,10 +518,22 @@ fn construct_meta_value_from_flag_array(
         .map(|(flag, value)| (*flag, value.unwrap_or_default()))
         .collect();
 
+    let mut binary_key = false;
     for (flag, token) in flag_array {
+        binary_key |= flag == b'b';
         map_meta_flag(flag, token, &mut meta_value)?;
     }
 
+    // Binary keys are returned base64-encoded, and are flagged as such with `b`.
+    if let (true, Some(key)) = (binary_key, &meta_value.key) {
+        let decoded = BASE64_STANDARD.decode(key).map_err(|_| {
+            Error::Protocol(Status::Error(ErrorKind::Generic(
+                "Invalid base64 key".to_string(),
+            )))
+        })?;
+        meta_value.key = Some(decoded);
+    }
+
     Ok(meta_value)
 }
 
@@ -626,6 +699,55 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_meta_debug_response() {
+        let input = b"ME foo exp=-1 la=3 cas=2 fetch=yes cls=1 size=63\r\nMN\r\n";
+        let (read_bytes, info) = parse_meta_debug_response(input).unwrap().unwrap();
+
+        assert_eq!(read_bytes, input.len() - 4);
+        assert_eq!(
+            info,
+            Some(MetaDebugInfo {
+                key: b"foo".to_vec(),
+                expiration: -1,
+                last_accessed: 3,
+                cas: 2,
+                fetched: true,
+                class_id: 1,
+                size: 63,
+            })
+        );
+
+        assert_eq!(parse_meta_debug_response(b"EN\r\n"), Ok(Some((4, None))));
+        assert_eq!(parse_meta_debug_response(b"ME foo exp=-1 la=3"), Ok(None));
+        assert!(parse_meta_debug_response(b"ME foo bogus\r\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_meta_get_data_value_with_empty_data_block() {
+        let input = b"VA 0 c42 W\r\n\r\nMN\r\n";
+        let (remaining, response) = parse_meta_get_data_value(input).unwrap();
+
+        // The empty data block is consumed along with the header.
+        assert_eq!(remaining, b"MN\r\n");
+
+        match response {
+            MetaResponse::Data(Some(meta_values)) => {
+                let meta_value = &meta_values[0];
+                assert_eq!(meta_value.data, None);
+                assert_eq!(meta_value.cas, Some(42));
+                assert_eq!(meta_value.is_recache_winner, Some(true));
+            }
+            _ => panic!("Expected Response::Data, got something else"),
+        }
+
+        // The empty data block may not have been received yet.
+        assert!(matches!(
+            parse_meta_get_data_value(b"VA 0 c42 W\r\n"),
+            Err(nom::Err::Incomplete(_))
+        ));
+    }
+
     #[test]
     fn test_parse_meta_get_data_value_tag_order_does_not_matter() {
         let input = b"VA 10 t2179 h1 l56 Oopaque-token\r\ntest-value\r\n";
@@ -757,6 +879,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_meta_get_data_value_decodes_binary_key() {
+        let input = b"VA 2 kAP8gCg== b\r\nhi\r\n";
+        let (remaining, response) = parse_meta_get_data_value(input).unwrap();
+
+        assert_eq!(remaining, b"");
+
+        match response {
+            MetaResponse::Data(Some(meta_values)) => {
+                assert_eq!(meta_values[0].key, Some(vec![0x00, 0xff, b' ', b'\n']));
+                assert_eq!(meta_values[0].data, Some(b"hi".to_vec()));
+            }
+            _ => panic!("Expected Response::Data, got something else"),
+        }
+
+        let input = b"VA 2 k!!! b\r\nhi\r\n";
+        assert!(parse_meta_get_data_value(input).is_err());
+    }
+
     #[test]
     fn test_parse_meta_get_data_value_cache_miss() {
         let input = b"EN\r\n";
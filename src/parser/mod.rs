@@ -11,13 +11,14 @@ use std::fmt;
 
 mod ascii_parser;
 pub use ascii_parser::{
-    parse_ascii_metadump_response, parse_ascii_response, parse_ascii_stats_response,
+    parse_ascii_line, parse_ascii_metadump_response, parse_ascii_response,
+    parse_ascii_stats_response,
 };
 
 mod meta_parser;
 pub use meta_parser::{
-    parse_meta_arithmetic_response, parse_meta_delete_response, parse_meta_get_response,
-    parse_meta_set_response,
+    parse_meta_arithmetic_response, parse_meta_debug_response, parse_meta_delete_response,
+    parse_meta_get_response, parse_meta_set_response,
 };
 
 /// A value from memcached generated by an ASCII protocol response.
@@ -101,6 +102,11 @@ pub enum ErrorKind {
     Server(String),
     /// An error from memcached related to a key that exceeds maximum allowed length (250 bytes).
     KeyTooLong,
+    /// A key that contains whitespace or control characters, which are not allowed by the protocol.
+    InvalidKey,
+    /// A value that exceeds the maximum item size of the server, which would be rejected by
+    /// memcached.
+    ValueTooLarge,
     /// An error from memcached related to a key that exceeds maximum allowed length (32 bytes).
     OpaqueTooLong,
 }
@@ -116,6 +122,32 @@ pub enum Response {
     IncrDecr(u64),
 }
 
+/// Outcome of a compare-and-swap (`cas`) operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CasOutcome {
+    /// The value was stored.
+    Stored,
+    /// The item has been modified since it was last fetched, so the value was not stored.
+    Exists,
+    /// The item does not exist, or has been deleted or expired since it was last fetched.
+    NotFound,
+}
+
+/// Outcome of a meta set (`ms`) operation.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MetaSetOutcome {
+    /// The value was stored.  Contains the metadata requested by the meta flags, if any were
+    /// requested and quiet mode was not used.
+    Stored(Option<MetaValue>),
+    /// The value was not stored because the condition of the set mode was not met, e.g. the key
+    /// already exists in add mode, or does not exist in replace, append or prepend mode.
+    NotStored,
+    /// The item's CAS value does not match the one given, so the value was not stored.
+    Exists,
+    /// A CAS value was given, but the item does not exist.
+    NotFound,
+}
+
 /// Response to a memcached meta protocol operation.
 #[derive(Clone, Debug, PartialEq)]
 pub enum MetaResponse {
@@ -166,6 +198,25 @@ pub struct KeyMetadata {
     pub size: u32,
 }
 
+/// Internal metadata for a key, as returned by a meta debug (`me`) operation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetaDebugInfo {
+    /// The key.
+    pub key: Vec<u8>,
+    /// Time until this key expires, in seconds, or -1 if it does not expire.
+    pub expiration: i64,
+    /// Time since this key was last accessed, in seconds.
+    pub last_accessed: u64,
+    /// CAS identifier.
+    pub cas: u64,
+    /// Whether or not this key has ever been fetched.
+    pub fetched: bool,
+    /// Slab class ID.
+    pub class_id: u32,
+    /// Size of the item, including its key and metadata, in bytes.
+    pub size: u32,
+}
+
 impl fmt::Display for Status {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -194,6 +245,8 @@ impl fmt::Display for ErrorKind {
             Self::Client(s) => write!(f, "client: {}", s),
             Self::Server(s) => write!(f, "server: {}", s),
             Self::KeyTooLong => write!(f, "Key exceeds maximum allowed length of 250 characters"),
+            Self::InvalidKey => write!(f, "Key contains whitespace or control characters"),
+            Self::ValueTooLarge => write!(f, "Value exceeds maximum item size"),
             Self::OpaqueTooLong => {
                 write!(f, "Opaque exceeds maximum allowed length of 32 characters")
             }
@@ -230,8 +283,10 @@ pub(crate) fn parse_incrdecr(buf: &[u8]) -> IResult<&[u8], Response> {
     terminated(map(parse_u64, Response::IncrDecr), crlf)(buf)
 }
 
+// Keys may contain any byte other than whitespace and control characters, including the bytes of
+// UTF-8 encoded characters.
 pub(crate) fn is_key_char(chr: u8) -> bool {
-    chr > 32 && chr < 127
+    chr > 32 && chr != 127
 }
 
 pub(crate) fn is_signed_digit(chr: u8) -> bool {
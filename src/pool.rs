@@ -0,0 +1,293 @@
+use std::collections::VecDeque;
+use std::io;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+use crate::{Client, ClientConfig, Error, MetaProtocol};
+
+/// Health check performed on an idle connection before it is handed out by a [`Pool`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HealthCheck {
+    /// Connections are handed out without being checked.
+    #[default]
+    None,
+    /// Connections are checked with a `version` command.
+    ///
+    /// Some proxies, such as Twemproxy, do not support this command.
+    Version,
+    /// Connections are checked with a meta no-op (`mn`) command.
+    ///
+    /// Available as of memcached 1.6.
+    MetaNoOp,
+}
+
+/// Configuration for a [`Pool`].
+#[derive(Clone, Debug)]
+pub struct PoolConfig {
+    /// Number of connections that are opened when the pool is created, and that are kept open
+    /// even when they exceed the idle timeout.
+    pub min_size: usize,
+    /// Maximum number of connections, idle or checked out, held by the pool at any time.
+    pub max_size: usize,
+    /// How long a connection may sit idle in the pool before it is closed.
+    ///
+    /// Connections are never closed for idling if this is `None`.
+    pub idle_timeout: Option<Duration>,
+    /// How long [`Pool::get`] may wait for a connection, including the time spent health checking
+    /// or opening one.
+    ///
+    /// [`Pool::get`] waits indefinitely if this is `None`.
+    pub checkout_timeout: Option<Duration>,
+    /// Health check performed on idle connections as they are checked out.
+    pub health_check: HealthCheck,
+    /// Configuration used for every connection opened by the pool.
+    pub client: ClientConfig,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        PoolConfig {
+            min_size: 0,
+            max_size: 10,
+            idle_timeout: Some(Duration::from_secs(300)),
+            checkout_timeout: Some(Duration::from_secs(30)),
+            health_check: HealthCheck::None,
+            client: ClientConfig::default(),
+        }
+    }
+}
+
+/// A snapshot of the connections held by a [`Pool`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolStatus {
+    /// Number of open connections waiting in the pool.
+    pub idle: usize,
+    /// Number of connections currently checked out of the pool.
+    pub checked_out: usize,
+}
+
+struct IdleClient {
+    client: Client,
+    idle_since: Instant,
+}
+
+struct PoolInner {
+    dsn: String,
+    config: PoolConfig,
+    idle: Mutex<VecDeque<IdleClient>>,
+    permits: Arc<Semaphore>,
+}
+
+/// A pool of [`Client`] connections to a single memcached server.
+///
+/// [`Pool`] is cheap to clone, and all clones share the same set of connections.  Connections are
+/// checked out with [`Pool::get`] and are returned to the pool when the resulting [`PooledClient`]
+/// is dropped, unless the client has been [poisoned](Client::is_poisoned), in which case the
+/// connection is discarded.
+#[derive(Clone)]
+pub struct Pool {
+    inner: Arc<PoolInner>,
+}
+
+impl Pool {
+    /// Creates a new [`Pool`] for the given data source string, opening `min_size` connections
+    /// up front.
+    ///
+    /// The DSN follows the same format as [`Client::new`].
+    pub async fn new<S: AsRef<str>>(dsn: S, config: PoolConfig) -> Result<Pool, Error> {
+        if config.max_size == 0 || config.min_size > config.max_size {
+            return Err(Error::Connect(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "pool max_size must be non-zero and no smaller than min_size",
+            )));
+        }
+
+        let dsn = dsn.as_ref().to_string();
+
+        let mut idle = VecDeque::with_capacity(config.max_size);
+        for _ in 0..config.min_size {
+            idle.push_back(IdleClient {
+                client: Client::with_config(&dsn, config.client.clone()).await?,
+                idle_since: Instant::now(),
+            });
+        }
+
+        Ok(Pool {
+            inner: Arc::new(PoolInner {
+                dsn,
+                permits: Arc::new(Semaphore::new(config.max_size)),
+                config,
+                idle: Mutex::new(idle),
+            }),
+        })
+    }
+
+    /// Checks out a connection from the pool.
+    ///
+    /// Idle connections are reused where possible, after passing the configured health check.
+    /// Otherwise, a new connection is opened if the pool has not reached `max_size`, or the call
+    /// waits for another connection to be returned.
+    ///
+    /// If no connection becomes available within `checkout_timeout`, [`Error::Timeout`] is
+    /// returned.
+    pub async fn get(&self) -> Result<PooledClient, Error> {
+        match self.inner.config.checkout_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, self.checkout())
+                .await
+                .map_err(|_| Error::Timeout)?,
+            None => self.checkout().await,
+        }
+    }
+
+    /// Returns the number of idle and checked out connections held by the pool.
+    pub fn status(&self) -> PoolStatus {
+        PoolStatus {
+            idle: self.inner.idle.lock().unwrap().len(),
+            checked_out: self.inner.config.max_size - self.inner.permits.available_permits(),
+        }
+    }
+
+    async fn checkout(&self) -> Result<PooledClient, Error> {
+        // Every checked out connection holds a permit, and new connections are only opened once
+        // there are no idle ones left, so the total number of connections never exceeds max_size.
+        let permit = self
+            .inner
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("pool semaphore is never closed");
+
+        while let Some(mut client) = self.inner.pop_idle() {
+            if self.inner.is_healthy(&mut client).await {
+                return Ok(PooledClient::new(client, self.inner.clone(), permit));
+            }
+        }
+
+        let client = Client::with_config(&self.inner.dsn, self.inner.config.client.clone()).await?;
+
+        Ok(PooledClient::new(client, self.inner.clone(), permit))
+    }
+}
+
+impl PoolInner {
+    fn pop_idle(&self) -> Option<Client> {
+        let mut idle = self.idle.lock().unwrap();
+
+        // Idle connections are returned to the back of the queue, so the oldest ones are at the
+        // front.  Close any that have expired, while keeping at least min_size connections open.
+        if let Some(idle_timeout) = self.config.idle_timeout {
+            while idle.len() > self.config.min_size
+                && idle
+                    .front()
+                    .is_some_and(|c| c.idle_since.elapsed() >= idle_timeout)
+            {
+                idle.pop_front();
+            }
+        }
+
+        idle.pop_back().map(|c| c.client)
+    }
+
+    async fn is_healthy(&self, client: &mut Client) -> bool {
+        match self.config.health_check {
+            HealthCheck::None => true,
+            HealthCheck::Version => client.version().await.is_ok(),
+            HealthCheck::MetaNoOp => client.meta_noop().await.is_ok(),
+        }
+    }
+}
+
+/// A [`Client`] checked out of a [`Pool`].
+///
+/// Dereferences to [`Client`], so all protocol operations are available on it directly.  The
+/// connection is returned to the pool when this is dropped.
+pub struct PooledClient {
+    client: Option<Client>,
+    pool: Arc<PoolInner>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl PooledClient {
+    fn new(client: Client, pool: Arc<PoolInner>, permit: OwnedSemaphorePermit) -> Self {
+        PooledClient {
+            client: Some(client),
+            pool,
+            _permit: permit,
+        }
+    }
+}
+
+impl Deref for PooledClient {
+    type Target = Client;
+
+    fn deref(&self) -> &Self::Target {
+        self.client.as_ref().expect("client is only taken on drop")
+    }
+}
+
+impl DerefMut for PooledClient {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.client.as_mut().expect("client is only taken on drop")
+    }
+}
+
+impl Drop for PooledClient {
+    fn drop(&mut self) {
+        if let Some(client) = self.client.take() {
+            if client.is_poisoned() {
+                return;
+            }
+
+            self.pool.idle.lock().unwrap().push_back(IdleClient {
+                client,
+                idle_since: Instant::now(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Pool, PoolConfig, PoolStatus};
+
+    #[tokio::test]
+    async fn test_rejects_zero_max_size() {
+        let config = PoolConfig {
+            max_size: 0,
+            ..Default::default()
+        };
+
+        assert!(Pool::new("tcp://127.0.0.1:11211", config).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_min_size_larger_than_max_size() {
+        let config = PoolConfig {
+            min_size: 2,
+            max_size: 1,
+            ..Default::default()
+        };
+
+        assert!(Pool::new("tcp://127.0.0.1:11211", config).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_empty_pool_status() {
+        let pool = Pool::new("tcp://127.0.0.1:11211", PoolConfig::default())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            pool.status(),
+            PoolStatus {
+                idle: 0,
+                checked_out: 0
+            }
+        );
+    }
+}
@@ -1,13 +1,15 @@
-use crate::{AsMemcachedValue, ErrorKind};
+use crate::{AsMemcachedValue, CasOutcome, ErrorKind};
 use crate::{Client, Error, Response, Status, Value};
 
 use fxhash::FxHashMap;
 use std::future::Future;
 use tokio::io::AsyncWriteExt;
 
-const MAX_KEY_LENGTH: usize = 250; // reference in memcached documentation: https://github.com/memcached/memcached/blob/5609673ed29db98a377749fab469fe80777de8fd/doc/protocol.txt#L46
-
 /// Trait defining ASCII protocol-specific methods for the Client.
+///
+/// Keys must be at most 250 bytes long, and must not contain whitespace or control characters.
+/// Single-key operations fail on an invalid key without sending anything, while multi-key
+/// operations skip invalid keys, reporting an error for each of them where results are per key.
 pub trait AsciiProtocol {
     /// Gets the given key.
     ///
@@ -39,6 +41,84 @@ pub trait AsciiProtocol {
         I: IntoIterator<Item = K>,
         K: AsRef<[u8]>;
 
+    /// Gets the given key along with its CAS identifier.
+    ///
+    /// If the key is found, `Some(Value)` is returned with `cas` populated, which can be passed to
+    /// [`AsciiProtocol::cas`] to perform an optimistic update.
+    ///
+    /// Otherwise, [`Error`] is returned.
+    fn gets<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+    ) -> impl Future<Output = Result<Option<Value>, Error>>;
+
+    /// Gets multiple keys along with their CAS identifiers.
+    ///
+    /// If any of the keys are found, a vector of [`Value`] with `cas` populated will be returned.
+    ///
+    /// Otherwise, [`Error`] is returned.
+    fn gets_multi<I, K>(&mut self, keys: I) -> impl Future<Output = Result<Vec<Value>, Error>>
+    where
+        I: IntoIterator<Item = K>,
+        K: AsRef<[u8]>;
+
+    /// Gets the given key and updates its TTL.
+    ///
+    /// If the key is found, `Some(Value)` is returned, describing the metadata and data of the key.
+    ///
+    /// Otherwise, [`Error`] is returned.
+    fn gat<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        ttl: i64,
+    ) -> impl Future<Output = Result<Option<Value>, Error>>;
+
+    /// Gets multiple keys and updates their TTLs.
+    ///
+    /// If any of the keys are found, a vector of [`Value`] will be returned. The same `ttl` is applied to each key.
+    ///
+    /// Otherwise, [`Error`] is returned.
+    fn gat_multi<I, K>(
+        &mut self,
+        keys: I,
+        ttl: i64,
+    ) -> impl Future<Output = Result<Vec<Value>, Error>>
+    where
+        I: IntoIterator<Item = K>,
+        K: AsRef<[u8]>;
+
+    /// Gets the given key along with its CAS identifier, and updates its TTL.
+    ///
+    /// If the key is found, `Some(Value)` is returned with `cas` populated.
+    ///
+    /// Otherwise, [`Error`] is returned.
+    fn gats<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        ttl: i64,
+    ) -> impl Future<Output = Result<Option<Value>, Error>>;
+
+    /// Gets multiple keys along with their CAS identifiers, and updates their TTLs.
+    ///
+    /// If any of the keys are found, a vector of [`Value`] with `cas` populated will be returned.
+    ///
+    /// Otherwise, [`Error`] is returned.
+    fn gats_multi<I, K>(
+        &mut self,
+        keys: I,
+        ttl: i64,
+    ) -> impl Future<Output = Result<Vec<Value>, Error>>
+    where
+        I: IntoIterator<Item = K>,
+        K: AsRef<[u8]>;
+
+    /// Updates the TTL of the given key without fetching or resending its value.
+    ///
+    /// If the key does not exist, Err(Protocol(NotFound)) is returned.
+    fn touch<K>(&mut self, key: K, ttl: i64) -> impl Future<Output = Result<(), Error>>
+    where
+        K: AsRef<[u8]>;
+
     /// Sets the given key.
     ///
     /// If `ttl` or `flags` are not specified, they will default to 0. If the value is set
@@ -94,6 +174,86 @@ pub trait AsciiProtocol {
         K: AsRef<[u8]> + Eq + std::hash::Hash + std::fmt::Debug,
         V: AsMemcachedValue;
 
+    /// Replaces the value of an existing key. If the key does not exist, Err(Protocol(NotStored)) is returned.
+    fn replace<K, V>(
+        &mut self,
+        key: K,
+        value: V,
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> impl Future<Output = Result<(), Error>>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue;
+
+    /// Attempts to replace multiple keys and values through pipelined commands.
+    ///
+    /// If `ttl` or `flags` are not specified, they will default to 0. The same values for `ttl` and `flags` will be applied to each key.
+    /// Returns a result with a HashMap of keys mapped to the result of the replace operation, or an error.
+    fn replace_multi<'a, K, V>(
+        &mut self,
+        kv: &'a [(K, V)],
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> impl Future<Output = Result<FxHashMap<&'a K, Result<(), Error>>, Error>>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash + std::fmt::Debug,
+        V: AsMemcachedValue;
+
+    /// Appends data to the value of an existing key. If the key does not exist, Err(Protocol(NotStored)) is returned.
+    ///
+    /// The TTL and flags of the existing item are left untouched.
+    fn append<K, V>(&mut self, key: K, value: V) -> impl Future<Output = Result<(), Error>>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue;
+
+    /// Attempts to append data to multiple keys through pipelined commands.
+    ///
+    /// Returns a result with a HashMap of keys mapped to the result of the append operation, or an error.
+    fn append_multi<'a, K, V>(
+        &mut self,
+        kv: &'a [(K, V)],
+    ) -> impl Future<Output = Result<FxHashMap<&'a K, Result<(), Error>>, Error>>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash + std::fmt::Debug,
+        V: AsMemcachedValue;
+
+    /// Prepends data to the value of an existing key. If the key does not exist, Err(Protocol(NotStored)) is returned.
+    ///
+    /// The TTL and flags of the existing item are left untouched.
+    fn prepend<K, V>(&mut self, key: K, value: V) -> impl Future<Output = Result<(), Error>>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue;
+
+    /// Attempts to prepend data to multiple keys through pipelined commands.
+    ///
+    /// Returns a result with a HashMap of keys mapped to the result of the prepend operation, or an error.
+    fn prepend_multi<'a, K, V>(
+        &mut self,
+        kv: &'a [(K, V)],
+    ) -> impl Future<Output = Result<FxHashMap<&'a K, Result<(), Error>>, Error>>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash + std::fmt::Debug,
+        V: AsMemcachedValue;
+
+    /// Stores the given key only if it has not been modified since `cas_unique` was fetched via [`AsciiProtocol::gets`].
+    ///
+    /// If `ttl` or `flags` are not specified, they will default to 0. The outcome of the operation is
+    /// returned as a [`CasOutcome`], otherwise [`Error`] is returned.
+    fn cas<K, V>(
+        &mut self,
+        key: K,
+        value: V,
+        cas_unique: u64,
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> impl Future<Output = Result<CasOutcome, Error>>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue;
+
     /// Delete multiple keys
     fn delete_multi_no_reply<K>(&mut self, keys: &[K]) -> impl Future<Output = Result<(), Error>>
     where
@@ -150,27 +310,7 @@ pub trait AsciiProtocol {
 
 impl AsciiProtocol for Client {
     async fn get<K: AsRef<[u8]>>(&mut self, key: K) -> Result<Option<Value>, Error> {
-        let kr = Self::validate_key_length(key.as_ref())?;
-
-        self.conn
-            .write_all(&[b"get ", kr, b"\r\n"].concat())
-            .await?;
-        self.conn.flush().await?;
-
-        match self.get_read_write_response().await? {
-            Response::Status(Status::NotFound) => Ok(None),
-            Response::Status(s) => Err(s.into()),
-            Response::Data(d) => d
-                .map(|mut items| {
-                    if items.len() != 1 {
-                        Err(Status::Error(ErrorKind::Protocol(None)).into())
-                    } else {
-                        Ok(items.remove(0))
-                    }
-                })
-                .transpose(),
-            _ => Err(Error::Protocol(Status::Error(ErrorKind::Protocol(None)))),
-        }
+        self.retrieval_command(b"get", key).await
     }
 
     async fn get_multi<I, K>(&mut self, keys: I) -> Result<Vec<Value>, Error>
@@ -178,22 +318,7 @@ impl AsciiProtocol for Client {
         I: IntoIterator<Item = K>,
         K: AsRef<[u8]>,
     {
-        self.conn.write_all(b"get").await?;
-        for key in keys {
-            if key.as_ref().len() > MAX_KEY_LENGTH {
-                continue;
-            }
-            self.conn.write_all(b" ").await?;
-            self.conn.write_all(key.as_ref()).await?;
-        }
-        self.conn.write_all(b"\r\n").await?;
-        self.conn.flush().await?;
-
-        match self.get_read_write_response().await? {
-            Response::Status(s) => Err(s.into()),
-            Response::Data(d) => d.ok_or(Status::NotFound.into()),
-            _ => Err(Status::Error(ErrorKind::Protocol(None)).into()),
-        }
+        self.retrieval_command_multi(b"get", keys).await
     }
 
     async fn get_many<I, K>(&mut self, keys: I) -> Result<Vec<Value>, Error>
@@ -204,48 +329,92 @@ impl AsciiProtocol for Client {
         self.get_multi(keys).await
     }
 
-    async fn set<K, V>(
-        &mut self,
-        key: K,
-        value: V,
-        ttl: Option<i64>,
-        flags: Option<u32>,
-    ) -> Result<(), Error>
+    async fn gets<K: AsRef<[u8]>>(&mut self, key: K) -> Result<Option<Value>, Error> {
+        self.retrieval_command(b"gets", key).await
+    }
+
+    async fn gets_multi<I, K>(&mut self, keys: I) -> Result<Vec<Value>, Error>
     where
+        I: IntoIterator<Item = K>,
         K: AsRef<[u8]>,
-        V: AsMemcachedValue,
     {
-        let kr = Self::validate_key_length(key.as_ref())?;
-        let vr = value.as_bytes();
+        self.retrieval_command_multi(b"gets", keys).await
+    }
 
-        self.conn.write_all(b"set ").await?;
-        self.conn.write_all(kr).await?;
+    async fn gat<K: AsRef<[u8]>>(&mut self, key: K, ttl: i64) -> Result<Option<Value>, Error> {
+        self.retrieval_command(format!("gat {}", ttl).as_bytes(), key)
+            .await
+    }
 
-        let flags = flags.unwrap_or(0).to_string();
-        self.conn.write_all(b" ").await?;
-        self.conn.write_all(flags.as_ref()).await?;
+    async fn gat_multi<I, K>(&mut self, keys: I, ttl: i64) -> Result<Vec<Value>, Error>
+    where
+        I: IntoIterator<Item = K>,
+        K: AsRef<[u8]>,
+    {
+        self.retrieval_command_multi(format!("gat {}", ttl).as_bytes(), keys)
+            .await
+    }
 
-        let ttl = ttl.unwrap_or(0).to_string();
-        self.conn.write_all(b" ").await?;
-        self.conn.write_all(ttl.as_ref()).await?;
+    async fn gats<K: AsRef<[u8]>>(&mut self, key: K, ttl: i64) -> Result<Option<Value>, Error> {
+        self.retrieval_command(format!("gats {}", ttl).as_bytes(), key)
+            .await
+    }
 
-        let vlen = vr.len().to_string();
-        self.conn.write_all(b" ").await?;
-        self.conn.write_all(vlen.as_ref()).await?;
-        self.conn.write_all(b"\r\n").await?;
+    async fn gats_multi<I, K>(&mut self, keys: I, ttl: i64) -> Result<Vec<Value>, Error>
+    where
+        I: IntoIterator<Item = K>,
+        K: AsRef<[u8]>,
+    {
+        self.retrieval_command_multi(format!("gats {}", ttl).as_bytes(), keys)
+            .await
+    }
+
+    async fn touch<K>(&mut self, key: K, ttl: i64) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        let kr = self.prepare_key(key.as_ref(), false)?;
 
-        self.conn.write_all(vr.as_ref()).await?;
-        self.conn.write_all(b"\r\n").await?;
+        self.begin_request().await?;
 
+        self.conn
+            .write_all(
+                &[
+                    b"touch ",
+                    kr.as_ref(),
+                    b" ",
+                    ttl.to_string().as_bytes(),
+                    b"\r\n",
+                ]
+                .concat(),
+            )
+            .await?;
         self.conn.flush().await?;
 
-        match self.get_read_write_response().await? {
-            Response::Status(Status::Stored) => Ok(()),
+        let response = self.get_read_write_response().await?;
+        self.end_request();
+
+        match response {
+            Response::Status(Status::Touched) => Ok(()),
             Response::Status(s) => Err(s.into()),
             _ => Err(Status::Error(ErrorKind::Protocol(None)).into()),
         }
     }
 
+    async fn set<K, V>(
+        &mut self,
+        key: K,
+        value: V,
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue,
+    {
+        self.storage_command(b"set", key, value, ttl, flags).await
+    }
+
     async fn set_multi<'a, K, V>(
         &mut self,
         kv: &'a [(K, V)],
@@ -256,38 +425,7 @@ impl AsciiProtocol for Client {
         K: AsRef<[u8]> + Eq + std::hash::Hash + std::fmt::Debug,
         V: AsMemcachedValue,
     {
-        for (key, value) in kv {
-            let kr = key.as_ref();
-            if kr.len() > MAX_KEY_LENGTH {
-                continue;
-            }
-
-            let vr = value.as_bytes();
-
-            self.conn.write_all(b"set ").await?;
-            self.conn.write_all(kr).await?;
-
-            let flags = flags.unwrap_or(0).to_string();
-            self.conn.write_all(b" ").await?;
-            self.conn.write_all(flags.as_ref()).await?;
-
-            let ttl = ttl.unwrap_or(0).to_string();
-            self.conn.write_all(b" ").await?;
-            self.conn.write_all(ttl.as_ref()).await?;
-
-            let vlen = vr.len().to_string();
-            self.conn.write_all(b" ").await?;
-            self.conn.write_all(vlen.as_ref()).await?;
-            self.conn.write_all(b"\r\n").await?;
-
-            self.conn.write_all(vr.as_ref()).await?;
-            self.conn.write_all(b"\r\n").await?;
-        }
-        self.conn.flush().await?;
-
-        let results = self.map_set_multi_responses(kv).await?;
-
-        Ok(results)
+        self.storage_command_multi(b"set", kv, ttl, flags).await
     }
 
     async fn add<K, V>(
@@ -301,38 +439,38 @@ impl AsciiProtocol for Client {
         K: AsRef<[u8]>,
         V: AsMemcachedValue,
     {
-        let kr = Self::validate_key_length(key.as_ref())?;
-        let vr = value.as_bytes();
-
-        self.conn.write_all(b"add ").await?;
-        self.conn.write_all(kr).await?;
-
-        let flags = flags.unwrap_or(0).to_string();
-        self.conn.write_all(b" ").await?;
-        self.conn.write_all(flags.as_ref()).await?;
-
-        let ttl = ttl.unwrap_or(0).to_string();
-        self.conn.write_all(b" ").await?;
-        self.conn.write_all(ttl.as_ref()).await?;
-
-        let vlen = vr.len().to_string();
-        self.conn.write_all(b" ").await?;
-        self.conn.write_all(vlen.as_ref()).await?;
-        self.conn.write_all(b"\r\n").await?;
-
-        self.conn.write_all(vr.as_ref()).await?;
-        self.conn.write_all(b"\r\n").await?;
+        self.storage_command(b"add", key, value, ttl, flags).await
+    }
 
-        self.conn.flush().await?;
+    async fn add_multi<'a, K, V>(
+        &mut self,
+        kv: &'a [(K, V)],
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> Result<FxHashMap<&'a K, Result<(), Error>>, Error>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash + std::fmt::Debug,
+        V: AsMemcachedValue,
+    {
+        self.storage_command_multi(b"add", kv, ttl, flags).await
+    }
 
-        match self.get_read_write_response().await? {
-            Response::Status(Status::Stored) => Ok(()),
-            Response::Status(s) => Err(s.into()),
-            _ => Err(Status::Error(ErrorKind::Protocol(None)).into()),
-        }
+    async fn replace<K, V>(
+        &mut self,
+        key: K,
+        value: V,
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue,
+    {
+        self.storage_command(b"replace", key, value, ttl, flags)
+            .await
     }
 
-    async fn add_multi<'a, K, V>(
+    async fn replace_multi<'a, K, V>(
         &mut self,
         kv: &'a [(K, V)],
         ttl: Option<i64>,
@@ -342,38 +480,83 @@ impl AsciiProtocol for Client {
         K: AsRef<[u8]> + Eq + std::hash::Hash + std::fmt::Debug,
         V: AsMemcachedValue,
     {
-        for (key, value) in kv {
-            let kr = key.as_ref();
-            if kr.len() > MAX_KEY_LENGTH {
-                continue;
-            }
+        self.storage_command_multi(b"replace", kv, ttl, flags).await
+    }
 
-            let vr = value.as_bytes();
+    // The server ignores the flags and exptime fields for append and prepend, but they are still
+    // required by the command syntax.
+    async fn append<K, V>(&mut self, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue,
+    {
+        self.storage_command(b"append", key, value, None, None)
+            .await
+    }
 
-            self.conn.write_all(b"add ").await?;
-            self.conn.write_all(kr).await?;
+    async fn append_multi<'a, K, V>(
+        &mut self,
+        kv: &'a [(K, V)],
+    ) -> Result<FxHashMap<&'a K, Result<(), Error>>, Error>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash + std::fmt::Debug,
+        V: AsMemcachedValue,
+    {
+        self.storage_command_multi(b"append", kv, None, None).await
+    }
 
-            let flags = flags.unwrap_or(0).to_string();
-            self.conn.write_all(b" ").await?;
-            self.conn.write_all(flags.as_ref()).await?;
+    async fn prepend<K, V>(&mut self, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue,
+    {
+        self.storage_command(b"prepend", key, value, None, None)
+            .await
+    }
 
-            let ttl = ttl.unwrap_or(0).to_string();
-            self.conn.write_all(b" ").await?;
-            self.conn.write_all(ttl.as_ref()).await?;
+    async fn prepend_multi<'a, K, V>(
+        &mut self,
+        kv: &'a [(K, V)],
+    ) -> Result<FxHashMap<&'a K, Result<(), Error>>, Error>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash + std::fmt::Debug,
+        V: AsMemcachedValue,
+    {
+        self.storage_command_multi(b"prepend", kv, None, None).await
+    }
 
-            let vlen = vr.len().to_string();
-            self.conn.write_all(b" ").await?;
-            self.conn.write_all(vlen.as_ref()).await?;
-            self.conn.write_all(b"\r\n").await?;
+    async fn cas<K, V>(
+        &mut self,
+        key: K,
+        value: V,
+        cas_unique: u64,
+        ttl: Option<i64>,
+        flags: Option<u32>,
+    ) -> Result<CasOutcome, Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue,
+    {
+        let kr = self.prepare_key(key.as_ref(), false)?;
+        let vr = value.as_bytes();
+        self.validate_value_size(vr.as_ref())?;
 
-            self.conn.write_all(vr.as_ref()).await?;
-            self.conn.write_all(b"\r\n").await?;
-        }
+        self.begin_request().await?;
+
+        self.write_storage_command(b"cas", &kr, vr.as_ref(), ttl, flags, Some(cas_unique))
+            .await?;
         self.conn.flush().await?;
 
-        let results = self.map_set_multi_responses(kv).await?;
+        let response = self.get_read_write_response().await?;
+        self.end_request();
 
-        Ok(results)
+        match response {
+            Response::Status(Status::Stored) => Ok(CasOutcome::Stored),
+            Response::Status(Status::Exists) => Ok(CasOutcome::Exists),
+            Response::Status(Status::NotFound) => Ok(CasOutcome::NotFound),
+            Response::Status(s) => Err(s.into()),
+            _ => Err(Status::Error(ErrorKind::Protocol(None)).into()),
+        }
     }
 
     /// Delete a key but don't wait for a reply.
@@ -381,12 +564,16 @@ impl AsciiProtocol for Client {
     where
         K: AsRef<[u8]>,
     {
-        let kr = Self::validate_key_length(key.as_ref())?;
+        let kr = self.prepare_key(key.as_ref(), false)?;
+
+        self.begin_request().await?;
 
         self.conn
-            .write_all(&[b"delete ", kr, b" noreply\r\n"].concat())
+            .write_all(&[b"delete ", kr.as_ref(), b" noreply\r\n"].concat())
             .await?;
         self.conn.flush().await?;
+        self.end_request();
+
         Ok(())
     }
 
@@ -395,14 +582,19 @@ impl AsciiProtocol for Client {
     where
         K: AsRef<[u8]>,
     {
-        let kr = Self::validate_key_length(key.as_ref())?;
+        let kr = self.prepare_key(key.as_ref(), false)?;
+
+        self.begin_request().await?;
 
         self.conn
-            .write_all(&[b"delete ", kr, b"\r\n"].concat())
+            .write_all(&[b"delete ", kr.as_ref(), b"\r\n"].concat())
             .await?;
         self.conn.flush().await?;
 
-        match self.get_read_write_response().await? {
+        let response = self.get_read_write_response().await?;
+        self.end_request();
+
+        match response {
             Response::Status(Status::Deleted) => Ok(()),
             Response::Status(s) => Err(s.into()),
             _ => Err(Status::Error(ErrorKind::Protocol(None)).into()),
@@ -413,17 +605,19 @@ impl AsciiProtocol for Client {
     where
         K: AsRef<[u8]>,
     {
+        self.begin_request().await?;
+
         for key in keys {
-            let kr = key.as_ref();
-            if kr.len() > MAX_KEY_LENGTH {
+            let Ok(kr) = self.prepare_key(key.as_ref(), false) else {
                 continue;
-            }
+            };
 
             self.conn.write_all(b"delete ").await?;
-            self.conn.write_all(kr).await?;
+            self.conn.write_all(&kr).await?;
             self.conn.write_all(b" noreply\r\n").await?;
         }
         self.conn.flush().await?;
+        self.end_request();
 
         Ok(())
     }
@@ -432,14 +626,28 @@ impl AsciiProtocol for Client {
     where
         K: AsRef<[u8]>,
     {
-        let kr = Self::validate_key_length(key.as_ref())?;
+        let kr = self.prepare_key(key.as_ref(), false)?;
+
+        self.begin_request().await?;
 
         self.conn
-            .write_all(&[b"incr ", kr, b" ", amount.to_string().as_bytes(), b"\r\n"].concat())
+            .write_all(
+                &[
+                    b"incr ",
+                    kr.as_ref(),
+                    b" ",
+                    amount.to_string().as_bytes(),
+                    b"\r\n",
+                ]
+                .concat(),
+            )
             .await?;
         self.conn.flush().await?;
 
-        match self.get_read_write_response().await? {
+        let response = self.get_read_write_response().await?;
+        self.end_request();
+
+        match response {
             Response::Status(s) => Err(s.into()),
             Response::IncrDecr(amount) => Ok(amount),
             _ => Err(Status::Error(ErrorKind::Protocol(None)).into()),
@@ -450,13 +658,15 @@ impl AsciiProtocol for Client {
     where
         K: AsRef<[u8]>,
     {
-        let kr = Self::validate_key_length(key.as_ref())?;
+        let kr = self.prepare_key(key.as_ref(), false)?;
+
+        self.begin_request().await?;
 
         self.conn
             .write_all(
                 &[
                     b"incr ",
-                    kr,
+                    kr.as_ref(),
                     b" ",
                     amount.to_string().as_bytes(),
                     b" noreply\r\n",
@@ -465,6 +675,7 @@ impl AsciiProtocol for Client {
             )
             .await?;
         self.conn.flush().await?;
+        self.end_request();
 
         Ok(())
     }
@@ -473,14 +684,28 @@ impl AsciiProtocol for Client {
     where
         K: AsRef<[u8]>,
     {
-        let kr = Self::validate_key_length(key.as_ref())?;
+        let kr = self.prepare_key(key.as_ref(), false)?;
+
+        self.begin_request().await?;
 
         self.conn
-            .write_all(&[b"decr ", kr, b" ", amount.to_string().as_bytes(), b"\r\n"].concat())
+            .write_all(
+                &[
+                    b"decr ",
+                    kr.as_ref(),
+                    b" ",
+                    amount.to_string().as_bytes(),
+                    b"\r\n",
+                ]
+                .concat(),
+            )
             .await?;
         self.conn.flush().await?;
 
-        match self.get_read_write_response().await? {
+        let response = self.get_read_write_response().await?;
+        self.end_request();
+
+        match response {
             Response::Status(s) => Err(s.into()),
             Response::IncrDecr(amount) => Ok(amount),
             _ => Err(Status::Error(ErrorKind::Protocol(None)).into()),
@@ -491,13 +716,15 @@ impl AsciiProtocol for Client {
     where
         K: AsRef<[u8]>,
     {
-        let kr = Self::validate_key_length(key.as_ref())?;
+        let kr = self.prepare_key(key.as_ref(), false)?;
+
+        self.begin_request().await?;
 
         self.conn
             .write_all(
                 &[
                     b"decr ",
-                    kr,
+                    kr.as_ref(),
                     b" ",
                     amount.to_string().as_bytes(),
                     b" noreply\r\n",
@@ -506,6 +733,7 @@ impl AsciiProtocol for Client {
             )
             .await?;
         self.conn.flush().await?;
+        self.end_request();
 
         Ok(())
     }
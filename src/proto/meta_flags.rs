@@ -0,0 +1,635 @@
+use std::fmt::Display;
+use std::io::Write;
+
+// Large enough for every flag a builder can set, with the widest possible tokens.
+const ENCODED_CAPACITY: usize = 128;
+
+/// Meta flags for [`MetaProtocol::meta_get_with`](crate::MetaProtocol::meta_get_with).
+///
+/// Only flags that are valid for `mg` can be set.  Quiet mode and opaque tokens are passed to the
+/// command directly.
+///
+/// ```
+/// use async_memcached::MetaGetFlags;
+///
+/// let flags = MetaGetFlags::new()
+///     .return_value()
+///     .return_cas()
+///     .return_ttl()
+///     .vivify_on_miss(30)
+///     .recache_if_ttl_below(10);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MetaGetFlags {
+    binary_key: bool,
+    return_value: bool,
+    return_cas: bool,
+    return_client_flags: bool,
+    return_hit: bool,
+    return_key: bool,
+    return_last_access: bool,
+    return_size: bool,
+    return_ttl: bool,
+    no_lru_bump: bool,
+    vivify_on_miss: Option<i64>,
+    recache_if_ttl_below: Option<i64>,
+    update_ttl: Option<i64>,
+}
+
+impl MetaGetFlags {
+    /// Creates an empty set of flags.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Treats the key as binary data (`b`).
+    ///
+    /// The key is base64-encoded on its way to the server, and a key returned by the server is
+    /// decoded, so arbitrary bytes can be used as a key.  The encoded key must still fit within the
+    /// maximum key length.
+    pub fn binary_key(mut self) -> Self {
+        self.binary_key = true;
+        self
+    }
+
+    /// Returns the item's value (`v`).
+    pub fn return_value(mut self) -> Self {
+        self.return_value = true;
+        self
+    }
+
+    /// Returns the item's CAS value (`c`).
+    pub fn return_cas(mut self) -> Self {
+        self.return_cas = true;
+        self
+    }
+
+    /// Returns the item's client flags (`f`).
+    pub fn return_client_flags(mut self) -> Self {
+        self.return_client_flags = true;
+        self
+    }
+
+    /// Returns whether the item has been hit before (`h`).
+    pub fn return_hit(mut self) -> Self {
+        self.return_hit = true;
+        self
+    }
+
+    /// Returns the item's key (`k`).
+    pub fn return_key(mut self) -> Self {
+        self.return_key = true;
+        self
+    }
+
+    /// Returns the time since the item was last accessed, in seconds (`l`).
+    pub fn return_last_access(mut self) -> Self {
+        self.return_last_access = true;
+        self
+    }
+
+    /// Returns the size of the item's value (`s`).
+    pub fn return_size(mut self) -> Self {
+        self.return_size = true;
+        self
+    }
+
+    /// Returns the item's remaining TTL in seconds, or -1 if it does not expire (`t`).
+    pub fn return_ttl(mut self) -> Self {
+        self.return_ttl = true;
+        self
+    }
+
+    /// Does not bump the item in the LRU (`u`).
+    pub fn no_lru_bump(mut self) -> Self {
+        self.no_lru_bump = true;
+        self
+    }
+
+    /// On a miss, creates an empty item with the given TTL in seconds and wins the right to
+    /// recache it (`N`).
+    pub fn vivify_on_miss(mut self, ttl: i64) -> Self {
+        self.vivify_on_miss = Some(ttl);
+        self
+    }
+
+    /// Wins the right to recache the item if its remaining TTL is below the given number of
+    /// seconds (`R`).
+    pub fn recache_if_ttl_below(mut self, ttl: i64) -> Self {
+        self.recache_if_ttl_below = Some(ttl);
+        self
+    }
+
+    /// Updates the item's TTL to the given number of seconds (`T`).
+    pub fn update_ttl(mut self, ttl: i64) -> Self {
+        self.update_ttl = Some(ttl);
+        self
+    }
+
+    pub(crate) fn encode(&self) -> EncodedMetaFlags {
+        let mut encoded = EncodedMetaFlags::default();
+        encoded.binary_key(self.binary_key);
+        encoded.flag(b'v', self.return_value);
+        encoded.flag(b'c', self.return_cas);
+        encoded.flag(b'f', self.return_client_flags);
+        encoded.flag(b'h', self.return_hit);
+        encoded.flag(b'k', self.return_key);
+        encoded.flag(b'l', self.return_last_access);
+        encoded.flag(b's', self.return_size);
+        encoded.flag(b't', self.return_ttl);
+        encoded.flag(b'u', self.no_lru_bump);
+        encoded.token(b'N', self.vivify_on_miss);
+        encoded.token(b'R', self.recache_if_ttl_below);
+        encoded.token(b'T', self.update_ttl);
+        encoded
+    }
+}
+
+/// Meta flags for [`MetaProtocol::meta_set_with`](crate::MetaProtocol::meta_set_with).
+///
+/// Only flags that are valid for `ms` can be set.  Quiet mode and opaque tokens are passed to the
+/// command directly.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MetaSetFlags {
+    binary_key: bool,
+    return_cas: bool,
+    return_key: bool,
+    return_size: bool,
+    invalidate: bool,
+    mode: Option<MetaSetMode>,
+    client_flags: Option<u32>,
+    ttl: Option<i64>,
+    compare_cas: Option<u64>,
+    explicit_cas: Option<u64>,
+    vivify_on_miss: Option<i64>,
+}
+
+impl MetaSetFlags {
+    /// Creates an empty set of flags.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Treats the key as binary data (`b`).
+    ///
+    /// The key is base64-encoded on its way to the server, and a key returned by the server is
+    /// decoded, so arbitrary bytes can be used as a key.  The encoded key must still fit within the
+    /// maximum key length.
+    pub fn binary_key(mut self) -> Self {
+        self.binary_key = true;
+        self
+    }
+
+    /// Returns the CAS value of the stored item (`c`).
+    pub fn return_cas(mut self) -> Self {
+        self.return_cas = true;
+        self
+    }
+
+    /// Returns the item's key (`k`).
+    pub fn return_key(mut self) -> Self {
+        self.return_key = true;
+        self
+    }
+
+    /// Returns the size of the stored value (`s`).
+    pub fn return_size(mut self) -> Self {
+        self.return_size = true;
+        self
+    }
+
+    /// Stores the given client flags with the item (`F`).
+    pub fn client_flags(mut self, flags: u32) -> Self {
+        self.client_flags = Some(flags);
+        self
+    }
+
+    /// Sets the item's TTL to the given number of seconds (`T`).
+    pub fn ttl(mut self, ttl: i64) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Stores the value according to the given mode, instead of unconditionally (`M`).
+    pub fn mode(mut self, mode: MetaSetMode) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Only stores the value if the item's CAS value matches the given one (`C`).
+    ///
+    /// Combined with [`invalidate`](MetaSetFlags::invalidate), a value is also stored if the given
+    /// CAS value is older than the item's and the item is stale.
+    pub fn compare_cas(mut self, cas: u64) -> Self {
+        self.compare_cas = Some(cas);
+        self
+    }
+
+    /// Stores the item with the given CAS value instead of one generated by the server (`E`).
+    pub fn explicit_cas(mut self, cas: u64) -> Self {
+        self.explicit_cas = Some(cas);
+        self
+    }
+
+    /// Marks the stored item as stale if the CAS comparison fails because the given CAS value is
+    /// older than the item's (`I`).
+    pub fn invalidate(mut self) -> Self {
+        self.invalidate = true;
+        self
+    }
+
+    /// In append or prepend mode, creates the item with the given TTL in seconds if it does not
+    /// exist (`N`).
+    pub fn vivify_on_miss(mut self, ttl: i64) -> Self {
+        self.vivify_on_miss = Some(ttl);
+        self
+    }
+
+    pub(crate) fn encode(&self) -> EncodedMetaFlags {
+        let mut encoded = EncodedMetaFlags::default();
+        encoded.binary_key(self.binary_key);
+        encoded.flag(b'c', self.return_cas);
+        encoded.flag(b'k', self.return_key);
+        encoded.flag(b's', self.return_size);
+        encoded.flag(b'I', self.invalidate);
+        encoded.token(b'M', self.mode.map(MetaSetMode::token));
+        encoded.token(b'F', self.client_flags);
+        encoded.token(b'T', self.ttl);
+        encoded.token(b'C', self.compare_cas);
+        encoded.token(b'E', self.explicit_cas);
+        encoded.token(b'N', self.vivify_on_miss);
+        encoded
+    }
+}
+
+/// Mode of a meta set operation, deciding whether the value is stored and how.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MetaSetMode {
+    /// Stores the value unconditionally.
+    #[default]
+    Set,
+    /// Only stores the value if the key does not exist.
+    Add,
+    /// Only stores the value if the key already exists.
+    Replace,
+    /// Appends the value to the existing value of the key.
+    Append,
+    /// Prepends the value to the existing value of the key.
+    Prepend,
+}
+
+impl MetaSetMode {
+    fn token(self) -> char {
+        match self {
+            MetaSetMode::Set => 'S',
+            MetaSetMode::Add => 'E',
+            MetaSetMode::Replace => 'R',
+            MetaSetMode::Append => 'A',
+            MetaSetMode::Prepend => 'P',
+        }
+    }
+}
+
+/// Meta flags for [`MetaProtocol::meta_delete_with`](crate::MetaProtocol::meta_delete_with).
+///
+/// Only flags that are valid for `md` can be set.  Quiet mode and opaque tokens are passed to the
+/// command directly.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MetaDeleteFlags {
+    binary_key: bool,
+    return_key: bool,
+    invalidate: bool,
+    remove_value: bool,
+    compare_cas: Option<u64>,
+    update_ttl: Option<i64>,
+}
+
+impl MetaDeleteFlags {
+    /// Creates an empty set of flags.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Treats the key as binary data (`b`).
+    ///
+    /// The key is base64-encoded on its way to the server, and a key returned by the server is
+    /// decoded, so arbitrary bytes can be used as a key.  The encoded key must still fit within the
+    /// maximum key length.
+    pub fn binary_key(mut self) -> Self {
+        self.binary_key = true;
+        self
+    }
+
+    /// Returns the item's key (`k`).
+    pub fn return_key(mut self) -> Self {
+        self.return_key = true;
+        self
+    }
+
+    /// Marks the item as stale instead of deleting it (`I`).
+    pub fn invalidate(mut self) -> Self {
+        self.invalidate = true;
+        self
+    }
+
+    /// Removes the item's value but keeps the item itself (`x`).
+    pub fn remove_value(mut self) -> Self {
+        self.remove_value = true;
+        self
+    }
+
+    /// Only deletes the item if its CAS value matches the given one (`C`).
+    pub fn compare_cas(mut self, cas: u64) -> Self {
+        self.compare_cas = Some(cas);
+        self
+    }
+
+    /// Updates the TTL of an invalidated item to the given number of seconds (`T`).
+    pub fn update_ttl(mut self, ttl: i64) -> Self {
+        self.update_ttl = Some(ttl);
+        self
+    }
+
+    pub(crate) fn encode(&self) -> EncodedMetaFlags {
+        let mut encoded = EncodedMetaFlags::default();
+        encoded.binary_key(self.binary_key);
+        encoded.flag(b'k', self.return_key);
+        encoded.flag(b'I', self.invalidate);
+        encoded.flag(b'x', self.remove_value);
+        encoded.token(b'C', self.compare_cas);
+        encoded.token(b'T', self.update_ttl);
+        encoded
+    }
+}
+
+/// Meta flags for [`MetaProtocol::meta_increment_with`](crate::MetaProtocol::meta_increment_with)
+/// and [`MetaProtocol::meta_decrement_with`](crate::MetaProtocol::meta_decrement_with).
+///
+/// Only flags that are valid for `ma` can be set.  The mode, delta, quiet mode and opaque tokens
+/// are passed to the command directly.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MetaArithmeticFlags {
+    binary_key: bool,
+    return_value: bool,
+    return_cas: bool,
+    return_key: bool,
+    return_ttl: bool,
+    vivify_on_miss: Option<i64>,
+    initial_value: Option<u64>,
+    compare_cas: Option<u64>,
+    update_ttl: Option<i64>,
+}
+
+impl MetaArithmeticFlags {
+    /// Creates an empty set of flags.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Treats the key as binary data (`b`).
+    ///
+    /// The key is base64-encoded on its way to the server, and a key returned by the server is
+    /// decoded, so arbitrary bytes can be used as a key.  The encoded key must still fit within the
+    /// maximum key length.
+    pub fn binary_key(mut self) -> Self {
+        self.binary_key = true;
+        self
+    }
+
+    /// Returns the item's new value (`v`).
+    pub fn return_value(mut self) -> Self {
+        self.return_value = true;
+        self
+    }
+
+    /// Returns the item's CAS value (`c`).
+    pub fn return_cas(mut self) -> Self {
+        self.return_cas = true;
+        self
+    }
+
+    /// Returns the item's key (`k`).
+    pub fn return_key(mut self) -> Self {
+        self.return_key = true;
+        self
+    }
+
+    /// Returns the item's remaining TTL in seconds, or -1 if it does not expire (`t`).
+    pub fn return_ttl(mut self) -> Self {
+        self.return_ttl = true;
+        self
+    }
+
+    /// On a miss, creates the item with the given TTL in seconds (`N`).
+    pub fn vivify_on_miss(mut self, ttl: i64) -> Self {
+        self.vivify_on_miss = Some(ttl);
+        self
+    }
+
+    /// The value of an item created on a miss, which defaults to 0 (`J`).
+    pub fn initial_value(mut self, value: u64) -> Self {
+        self.initial_value = Some(value);
+        self
+    }
+
+    /// Only updates the item if its CAS value matches the given one (`C`).
+    pub fn compare_cas(mut self, cas: u64) -> Self {
+        self.compare_cas = Some(cas);
+        self
+    }
+
+    /// Updates the item's TTL to the given number of seconds (`T`).
+    pub fn update_ttl(mut self, ttl: i64) -> Self {
+        self.update_ttl = Some(ttl);
+        self
+    }
+
+    pub(crate) fn encode(&self) -> EncodedMetaFlags {
+        let mut encoded = EncodedMetaFlags::default();
+        encoded.binary_key(self.binary_key);
+        encoded.flag(b'v', self.return_value);
+        encoded.flag(b'c', self.return_cas);
+        encoded.flag(b'k', self.return_key);
+        encoded.flag(b't', self.return_ttl);
+        encoded.token(b'N', self.vivify_on_miss);
+        encoded.token(b'J', self.initial_value);
+        encoded.token(b'C', self.compare_cas);
+        encoded.token(b'T', self.update_ttl);
+        encoded
+    }
+}
+
+/// Meta flags serialized into a fixed-size buffer, each preceded by a space, ready to be written
+/// after a command's key.
+pub(crate) struct EncodedMetaFlags {
+    buf: [u8; ENCODED_CAPACITY],
+    len: usize,
+    binary_key: bool,
+}
+
+impl Default for EncodedMetaFlags {
+    fn default() -> Self {
+        EncodedMetaFlags {
+            buf: [0; ENCODED_CAPACITY],
+            len: 0,
+            binary_key: false,
+        }
+    }
+}
+
+impl EncodedMetaFlags {
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    pub(crate) fn is_binary_key(&self) -> bool {
+        self.binary_key
+    }
+
+    fn binary_key(&mut self, enabled: bool) {
+        self.flag(b'b', enabled);
+        self.binary_key = enabled;
+    }
+
+    fn flag(&mut self, flag: u8, enabled: bool) {
+        if enabled {
+            self.buf[self.len..self.len + 2].copy_from_slice(&[b' ', flag]);
+            self.len += 2;
+        }
+    }
+
+    fn token<T: Display>(&mut self, flag: u8, token: Option<T>) {
+        if let Some(token) = token {
+            let mut rest = &mut self.buf[self.len..];
+            let available = rest.len();
+            write!(rest, " {}{}", flag as char, token)
+                .expect("encoded meta flags exceed the buffer capacity");
+            self.len += available - rest.len();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_flags_encode_to_nothing() {
+        assert_eq!(MetaGetFlags::new().encode().as_bytes(), b"");
+        assert_eq!(MetaSetFlags::new().encode().as_bytes(), b"");
+        assert_eq!(MetaDeleteFlags::new().encode().as_bytes(), b"");
+        assert_eq!(MetaArithmeticFlags::new().encode().as_bytes(), b"");
+    }
+
+    #[test]
+    fn test_get_flags_encoding() {
+        let flags = MetaGetFlags::new()
+            .return_value()
+            .return_cas()
+            .return_ttl()
+            .vivify_on_miss(30)
+            .recache_if_ttl_below(10);
+
+        assert_eq!(flags.encode().as_bytes(), b" v c t N30 R10");
+    }
+
+    #[test]
+    fn test_set_flags_encoding() {
+        let flags = MetaSetFlags::new()
+            .mode(MetaSetMode::Add)
+            .client_flags(3)
+            .ttl(60)
+            .return_cas();
+        assert_eq!(flags.encode().as_bytes(), b" c ME F3 T60");
+
+        let flags = MetaSetFlags::new()
+            .mode(MetaSetMode::Append)
+            .vivify_on_miss(30);
+        assert_eq!(flags.encode().as_bytes(), b" MA N30");
+
+        let flags = MetaSetFlags::new()
+            .compare_cas(5)
+            .explicit_cas(6)
+            .invalidate();
+        assert_eq!(flags.encode().as_bytes(), b" I C5 E6");
+    }
+
+    #[test]
+    fn test_binary_key_flag() {
+        let flags = MetaDeleteFlags::new().binary_key().encode();
+        assert_eq!(flags.as_bytes(), b" b");
+        assert!(flags.is_binary_key());
+
+        assert!(!MetaDeleteFlags::new().encode().is_binary_key());
+    }
+
+    #[test]
+    fn test_setting_a_flag_twice_encodes_it_once() {
+        let flags = MetaSetFlags::new()
+            .ttl(10)
+            .return_cas()
+            .ttl(-1)
+            .return_cas();
+
+        assert_eq!(flags.encode().as_bytes(), b" c T-1");
+    }
+
+    #[test]
+    fn test_widest_flags_fit_in_buffer() {
+        let get = MetaGetFlags::new()
+            .binary_key()
+            .return_value()
+            .return_cas()
+            .return_client_flags()
+            .return_hit()
+            .return_key()
+            .return_last_access()
+            .return_size()
+            .return_ttl()
+            .no_lru_bump()
+            .vivify_on_miss(i64::MIN)
+            .recache_if_ttl_below(i64::MIN)
+            .update_ttl(i64::MIN);
+        assert_eq!(
+            get.encode().as_bytes(),
+            format!(" b v c f h k l s t u N{0} R{0} T{0}", i64::MIN).as_bytes()
+        );
+
+        let set = MetaSetFlags::new()
+            .binary_key()
+            .return_cas()
+            .return_key()
+            .return_size()
+            .invalidate()
+            .mode(MetaSetMode::Prepend)
+            .client_flags(u32::MAX)
+            .ttl(i64::MIN)
+            .compare_cas(u64::MAX)
+            .explicit_cas(u64::MAX)
+            .vivify_on_miss(i64::MIN);
+        assert_eq!(
+            set.encode().as_bytes(),
+            format!(
+                " b c k s I MP F{} T{1} C{2} E{2} N{1}",
+                u32::MAX,
+                i64::MIN,
+                u64::MAX
+            )
+            .as_bytes()
+        );
+
+        let arithmetic = MetaArithmeticFlags::new()
+            .return_value()
+            .return_cas()
+            .return_key()
+            .return_ttl()
+            .vivify_on_miss(i64::MIN)
+            .initial_value(u64::MAX)
+            .compare_cas(u64::MAX)
+            .update_ttl(i64::MIN);
+        assert_eq!(
+            arithmetic.encode().as_bytes(),
+            format!(" v c k t N{0} J{1} C{1} T{0}", i64::MIN, u64::MAX).as_bytes()
+        );
+    }
+}
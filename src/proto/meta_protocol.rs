@@ -1,16 +1,39 @@
-use crate::{AsMemcachedValue, Client, Error, Status};
+use crate::{
+    has_binary_key_flag, retrieved_meta_value, AsMemcachedValue, Client, Error, ErrorKind,
+    MetaSetOutcome, Status,
+};
 
 use crate::parser::{
-    parse_meta_arithmetic_response, parse_meta_delete_response, parse_meta_get_response,
-    parse_meta_set_response,
+    parse_meta_arithmetic_response, parse_meta_debug_response, parse_meta_delete_response,
+    parse_meta_get_response, parse_meta_set_response,
+};
+use crate::parser::{MetaDebugInfo, MetaResponse, MetaValue};
+use crate::proto::meta_flags::{
+    EncodedMetaFlags, MetaArithmeticFlags, MetaDeleteFlags, MetaGetFlags, MetaSetFlags,
 };
-use crate::parser::{MetaResponse, MetaValue};
 
 use std::future::Future;
+use std::time::Duration;
+
+use fxhash::FxHashMap;
 
 use tokio::io::AsyncWriteExt;
 
+// Bounds on how long `get_or_recompute` waits between lookups while another caller recomputes a
+// missing value.
+const RECOMPUTE_INITIAL_BACKOFF: Duration = Duration::from_millis(5);
+const RECOMPUTE_MAX_BACKOFF: Duration = Duration::from_millis(100);
+
+/// An item to store with [`MetaProtocol::meta_set_multi`]: a key, its value, and optionally its TTL
+/// in seconds and its client flags.
+pub type MetaSetItem<K, V> = (K, V, Option<i64>, Option<u32>);
+
 /// Trait defining Meta protocol-specific methods for the Client.
+///
+/// Keys are treated as binary data when the `b` meta flag is given, or when the `binary_key` flag
+/// is set on one of the typed flag builders.  Binary keys are base64-encoded on their way to the
+/// server and decoded when returned in [`MetaValue::key`], so any bytes can be used as a key,
+/// provided the encoded key fits within the maximum key length.
 pub trait MetaProtocol {
     /// Gets the given key with additional metadata.
     ///
@@ -35,6 +58,42 @@ pub trait MetaProtocol {
         meta_flags: Option<&[&str]>,
     ) -> impl Future<Output = Result<Option<MetaValue>, Error>>;
 
+    /// Gets the given key with additional metadata, using typed meta flags.
+    ///
+    /// Behaves like [`meta_get`](MetaProtocol::meta_get), with the meta flags built by
+    /// [`MetaGetFlags`] instead of passed as strings.
+    fn meta_get_with<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        flags: &MetaGetFlags,
+    ) -> impl Future<Output = Result<Option<MetaValue>, Error>>;
+
+    /// Gets multiple keys with additional metadata, in a single round-trip.
+    ///
+    /// Returns a map from each key that was found to its `MetaValue`, populated as requested by the
+    /// meta flags.  Keys that are not found, or that are invalid because they exceed the maximum key
+    /// length or contain whitespace or control characters, are absent from the map.
+    //
+    // Command format:
+    // mg <key> O<index> <meta_flags>* q\r\n   (once per key)
+    // mn\r\n
+    //
+    // - <index> is the position of the key in `keys`, sent as an opaque token so that each hit can
+    // be matched back to its key.  Misses are suppressed by quiet mode, and the no-op command marks
+    // the end of the batch.
+    //
+    // - <meta_flags> is an optional slice of string references for meta flags, applied to every key.
+    // "O" and "q" flags are ignored.
+    fn meta_get_multi<'a, K>(
+        &mut self,
+        keys: &'a [K],
+        meta_flags: Option<&[&str]>,
+    ) -> impl Future<Output = Result<FxHashMap<&'a K, MetaValue>, Error>>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash;
+
     /// Sets the given key with additional metadata.
     ///
     /// If the value is set successfully, `Some(MetaValue)` is returned, otherwise [`Error`] is returned.
@@ -64,6 +123,49 @@ pub trait MetaProtocol {
         K: AsRef<[u8]>,
         V: AsMemcachedValue;
 
+    /// Sets the given key with additional metadata, using typed meta flags.
+    ///
+    /// Behaves like [`meta_set`](MetaProtocol::meta_set), with the meta flags built by
+    /// [`MetaSetFlags`] instead of passed as strings, including the set mode and CAS options.
+    /// Whether the value was stored is returned as a [`MetaSetOutcome`], while [`Error`] is only
+    /// returned if the operation itself failed.
+    fn meta_set_with<K, V>(
+        &mut self,
+        key: K,
+        value: V,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        flags: &MetaSetFlags,
+    ) -> impl Future<Output = Result<MetaSetOutcome, Error>>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue;
+
+    /// Sets multiple keys with additional metadata, in a single round-trip.
+    ///
+    /// Each item carries its own value, and optionally its own TTL and client flags.  Returns a map
+    /// from each key to the result of storing it.  Successes are suppressed by the server in quiet
+    /// mode, so only failures are read back; keys that exceed the maximum key length, or that contain
+    /// whitespace or control characters, are not sent and fail on their own.
+    //
+    // Command format:
+    // ms <key> <datalen> O<index> T<ttl>? F<flags>? <meta_flags>* q\r\n<data_block>\r\n   (once per item)
+    // mn\r\n
+    //
+    // - <index> is the position of the item in `items`, sent as an opaque token so that each
+    // failure can be matched back to its key.  The no-op command marks the end of the batch.
+    //
+    // - <meta_flags> is an optional slice of string references for meta flags, applied to every item,
+    // e.g. "I" to invalidate or "MA" to append.  "O", "q", "T" and "F" flags are ignored.
+    fn meta_set_multi<'a, K, V>(
+        &mut self,
+        items: &'a [MetaSetItem<K, V>],
+        meta_flags: Option<&[&str]>,
+    ) -> impl Future<Output = Result<FxHashMap<&'a K, Result<(), Error>>, Error>>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash,
+        V: AsMemcachedValue;
+
     /// Deletes the given key with additional metadata.
     ///
     /// If the key is found, it will be deleted, invalidated or tombstoned depending on the meta flags provided.
@@ -84,6 +186,43 @@ pub trait MetaProtocol {
         meta_flags: Option<&[&str]>,
     ) -> impl Future<Output = Result<Option<MetaValue>, Error>>;
 
+    /// Deletes the given key with additional metadata, using typed meta flags.
+    ///
+    /// Behaves like [`meta_delete`](MetaProtocol::meta_delete), with the meta flags built by
+    /// [`MetaDeleteFlags`] instead of passed as strings.
+    fn meta_delete_with<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        flags: &MetaDeleteFlags,
+    ) -> impl Future<Output = Result<Option<MetaValue>, Error>>;
+
+    /// Deletes multiple keys with additional metadata, in a single round-trip.
+    ///
+    /// Returns a map from each key to the result of deleting it.  Successes are suppressed by the
+    /// server in quiet mode, so only failures are read back; keys that exceed the maximum key length,
+    /// or that contain whitespace or control characters, are not sent and fail on their own.
+    /// Memcached also suppresses not found responses in
+    /// quiet mode, so deleting a key that does not exist is usually reported as a success.
+    //
+    // Command format:
+    // md <key> O<index> <meta_flags>* q\r\n   (once per key)
+    // mn\r\n
+    //
+    // - <index> is the position of the key in `keys`, sent as an opaque token so that each failure
+    // can be matched back to its key.  The no-op command marks the end of the batch.
+    //
+    // - <meta_flags> is an optional slice of string references for meta flags, applied to every key.
+    // "O" and "q" flags are ignored.
+    fn meta_delete_multi<'a, K>(
+        &mut self,
+        keys: &'a [K],
+        meta_flags: Option<&[&str]>,
+    ) -> impl Future<Output = Result<FxHashMap<&'a K, Result<(), Error>>, Error>>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash;
+
     /// Performs an increment (arithmetic) operation on the given key.
     ///
     /// If the key is found, the increment operation is performed.
@@ -116,6 +255,19 @@ pub trait MetaProtocol {
         meta_flags: Option<&[&str]>,
     ) -> impl Future<Output = Result<Option<MetaValue>, Error>>;
 
+    /// Performs an increment (arithmetic) operation on the given key, using typed meta flags.
+    ///
+    /// Behaves like [`meta_increment`](MetaProtocol::meta_increment), with the meta flags built by
+    /// [`MetaArithmeticFlags`] instead of passed as strings.
+    fn meta_increment_with<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        delta: Option<u64>,
+        flags: &MetaArithmeticFlags,
+    ) -> impl Future<Output = Result<Option<MetaValue>, Error>>;
+
     /// Performs a decrement (arithmetic) operation on the given key.
     ///
     /// If the key is found, the decrement operation is performed.
@@ -147,6 +299,150 @@ pub trait MetaProtocol {
         delta: Option<u64>,
         meta_flags: Option<&[&str]>,
     ) -> impl Future<Output = Result<Option<MetaValue>, Error>>;
+
+    /// Performs a decrement (arithmetic) operation on the given key, using typed meta flags.
+    ///
+    /// Behaves like [`meta_decrement`](MetaProtocol::meta_decrement), with the meta flags built by
+    /// [`MetaArithmeticFlags`] instead of passed as strings.
+    fn meta_decrement_with<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        delta: Option<u64>,
+        flags: &MetaArithmeticFlags,
+    ) -> impl Future<Output = Result<Option<MetaValue>, Error>>;
+
+    /// Sends a no-op and waits for the server to acknowledge it.
+    ///
+    /// Useful as a cheap health check, and supported by proxies that do not implement `version`.
+    //
+    // Command format:
+    // mn\r\n
+    fn meta_noop(&mut self) -> impl Future<Output = Result<(), Error>>;
+
+    /// Gets internal metadata for the given key, without fetching its value or bumping it in the
+    /// LRU.
+    ///
+    /// If the key is found, `Some(MetaDebugInfo)` is returned.  Otherwise, `None` is returned.
+    //
+    // Command format:
+    // me <key>\r\n
+    //
+    // - <key> is the key string, with a maximum length of 250 bytes.
+    fn meta_debug<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+    ) -> impl Future<Output = Result<Option<MetaDebugInfo>, Error>>;
+
+    /// Gets the value of the given key, recomputing it with `loader` when it is missing, stale, or
+    /// has less than `recache_threshold` seconds left to live.
+    ///
+    /// Uses the meta protocol's recache and vivify flags so that only one caller at a time wins the
+    /// right to recompute a value.  The winner calls `loader`, stores its value with the given `ttl`
+    /// in seconds and returns it, while other callers are served the current, possibly stale,
+    /// value in the meantime.  On a miss there is no value to serve, so other callers wait for the
+    /// winner's value, for up to `recache_threshold` seconds if the winner never stores one.
+    ///
+    /// If `loader` fails, its error is returned and nothing is stored.
+    //
+    // Command format:
+    // mg <key> v c N<recache_threshold> R<recache_threshold>\r\n
+    // ms <key> <datalen> T<ttl> C<cas>\r\n<data_block>\r\n   (winner only)
+    fn get_or_recompute<K, F, Fut, V, E>(
+        &mut self,
+        key: K,
+        ttl: i64,
+        recache_threshold: i64,
+        loader: F,
+    ) -> impl Future<Output = Result<Vec<u8>, E>>
+    where
+        K: AsRef<[u8]>,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V, E>>,
+        V: AsMemcachedValue,
+        E: From<Error>,
+    {
+        async move {
+            let key = key.as_ref();
+            // The placeholder created on a miss must expire, so that another caller takes over if
+            // the winner never stores a value, while a TTL of 0 would never expire.
+            let lookup = MetaGetFlags::new()
+                .return_value()
+                .return_cas()
+                .vivify_on_miss(recache_threshold.max(1))
+                .recache_if_ttl_below(recache_threshold);
+            let mut backoff = RECOMPUTE_INITIAL_BACKOFF;
+
+            loop {
+                let item = self.meta_get_with(key, false, None, &lookup).await?;
+
+                match item {
+                    Some(item) if item.is_recache_winner != Some(true) => {
+                        // Another caller is recomputing a value that was just created on a miss.
+                        if item.is_recache_winner == Some(false) && item.data.is_none() {
+                            tokio::time::sleep(backoff).await;
+                            backoff = (backoff * 2).min(RECOMPUTE_MAX_BACKOFF);
+                            continue;
+                        }
+
+                        return Ok(item.data.unwrap_or_default());
+                    }
+                    item => {
+                        let cas = item.as_ref().and_then(|item| item.cas);
+                        let is_placeholder = item.and_then(|item| item.data).is_none();
+
+                        let value = match loader().await {
+                            Ok(value) => value,
+                            Err(e) => {
+                                // Drop the placeholder so that a waiting caller can take over
+                                // straight away.  Failing to do so only delays them.
+                                if let (true, Some(cas)) = (is_placeholder, cas) {
+                                    let flags = MetaDeleteFlags::new().compare_cas(cas);
+                                    let _ = self.meta_delete_with(key, false, None, &flags).await;
+                                }
+                                return Err(e);
+                            }
+                        };
+
+                        // The value is only stored if the item has not changed since it was
+                        // fetched, e.g. by being invalidated again.  Either way, the freshly loaded
+                        // value is returned.
+                        let value = value.as_bytes();
+                        let mut store = MetaSetFlags::new().ttl(ttl);
+                        if let Some(cas) = cas {
+                            store = store.compare_cas(cas);
+                        }
+                        self.meta_set_with(key, value.as_ref(), false, None, &store)
+                            .await?;
+
+                        return Ok(value.into_owned());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Invalidates the given key, marking it as stale instead of deleting it.
+    ///
+    /// The next caller of [`get_or_recompute`](MetaProtocol::get_or_recompute) recomputes the
+    /// value, while the stale value is served to other callers in the meantime.  Invalidating a key
+    /// that does not exist succeeds.
+    //
+    // Command format:
+    // md <key> I\r\n
+    fn meta_invalidate<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+    ) -> impl Future<Output = Result<(), Error>> {
+        async move {
+            let flags = MetaDeleteFlags::new().invalidate();
+            match self.meta_delete_with(key, false, None, &flags).await {
+                Ok(_) | Err(Error::Protocol(Status::NotFound)) => Ok(()),
+                Err(e) => Err(e),
+            }
+        }
+    }
 }
 
 impl MetaProtocol for Client {
@@ -157,25 +453,66 @@ impl MetaProtocol for Client {
         opaque: Option<&[u8]>,
         meta_flags: Option<&[&str]>,
     ) -> Result<Option<MetaValue>, Error> {
-        let kr = Self::validate_key_length(key.as_ref())?;
-
-        if let Some(opaque) = &opaque {
-            Self::validate_opaque_length(opaque)?;
-        }
-
-        self.conn.write_all(b"mg ").await?;
-        self.conn.write_all(kr).await?;
-
-        Self::check_and_write_opaque(self, opaque).await?;
-
-        Self::check_and_write_meta_flags(self, meta_flags, opaque).await?;
+        self.meta_get_command(
+            key.as_ref(),
+            is_quiet,
+            opaque,
+            MetaFlagsArg::Strings(meta_flags),
+        )
+        .await
+    }
 
-        Self::check_and_write_quiet_mode(self, is_quiet).await?;
+    async fn meta_get_with<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        flags: &MetaGetFlags,
+    ) -> Result<Option<MetaValue>, Error> {
+        self.meta_get_command(
+            key.as_ref(),
+            is_quiet,
+            opaque,
+            MetaFlagsArg::Encoded(flags.encode()),
+        )
+        .await
+    }
 
-        self.conn.flush().await?;
+    async fn meta_get_multi<'a, K>(
+        &mut self,
+        keys: &'a [K],
+        meta_flags: Option<&[&str]>,
+    ) -> Result<FxHashMap<&'a K, MetaValue>, Error>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash,
+    {
+        self.meta_get_multi_command(keys, meta_flags).await
+    }
 
-        match self.drive_receive(parse_meta_get_response).await? {
-            MetaResponse::Status(Status::NotFound) => Ok(None),
+    async fn meta_set<K, V>(
+        &mut self,
+        key: K,
+        value: V,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        meta_flags: Option<&[&str]>,
+    ) -> Result<Option<MetaValue>, Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsMemcachedValue,
+    {
+        let response = self
+            .meta_set_command(
+                key.as_ref(),
+                value.as_bytes().as_ref(),
+                is_quiet,
+                opaque,
+                MetaFlagsArg::Strings(meta_flags),
+            )
+            .await?;
+
+        match response {
+            MetaResponse::Status(Status::Stored) => Ok(None),
             MetaResponse::Status(Status::NoOp) => Ok(None),
             MetaResponse::Status(s) => Err(s.into()),
             MetaResponse::Data(d) => d
@@ -187,28 +524,300 @@ impl MetaProtocol for Client {
         }
     }
 
-    async fn meta_set<K, V>(
+    async fn meta_set_with<K, V>(
         &mut self,
         key: K,
         value: V,
         is_quiet: bool,
         opaque: Option<&[u8]>,
-        meta_flags: Option<&[&str]>,
-    ) -> Result<Option<MetaValue>, Error>
+        flags: &MetaSetFlags,
+    ) -> Result<MetaSetOutcome, Error>
     where
         K: AsRef<[u8]>,
         V: AsMemcachedValue,
     {
-        let kr = Self::validate_key_length(key.as_ref())?;
+        let response = self
+            .meta_set_command(
+                key.as_ref(),
+                value.as_bytes().as_ref(),
+                is_quiet,
+                opaque,
+                MetaFlagsArg::Encoded(flags.encode()),
+            )
+            .await?;
+
+        match response {
+            MetaResponse::Status(Status::Stored) | MetaResponse::Status(Status::NoOp) => {
+                Ok(MetaSetOutcome::Stored(None))
+            }
+            MetaResponse::Status(s) => meta_set_failure(s),
+            MetaResponse::Data(Some(mut items)) if items.len() == 1 => {
+                let item = items.remove(0);
+                match item.status {
+                    Some(Status::Stored) => Ok(MetaSetOutcome::Stored(Some(item))),
+                    Some(s) => meta_set_failure(s),
+                    None => Err(Status::Error(ErrorKind::Protocol(None)).into()),
+                }
+            }
+            MetaResponse::Data(_) => Err(Status::Error(ErrorKind::Protocol(None)).into()),
+        }
+    }
+
+    async fn meta_set_multi<'a, K, V>(
+        &mut self,
+        items: &'a [MetaSetItem<K, V>],
+        meta_flags: Option<&[&str]>,
+    ) -> Result<FxHashMap<&'a K, Result<(), Error>>, Error>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash,
+        V: AsMemcachedValue,
+    {
+        self.meta_set_multi_command(items, meta_flags).await
+    }
+
+    async fn meta_delete<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        meta_flags: Option<&[&str]>,
+    ) -> Result<Option<MetaValue>, Error> {
+        self.meta_delete_command(
+            key.as_ref(),
+            is_quiet,
+            opaque,
+            MetaFlagsArg::Strings(meta_flags),
+        )
+        .await
+    }
+
+    async fn meta_delete_with<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        flags: &MetaDeleteFlags,
+    ) -> Result<Option<MetaValue>, Error> {
+        self.meta_delete_command(
+            key.as_ref(),
+            is_quiet,
+            opaque,
+            MetaFlagsArg::Encoded(flags.encode()),
+        )
+        .await
+    }
+
+    async fn meta_delete_multi<'a, K>(
+        &mut self,
+        keys: &'a [K],
+        meta_flags: Option<&[&str]>,
+    ) -> Result<FxHashMap<&'a K, Result<(), Error>>, Error>
+    where
+        K: AsRef<[u8]> + Eq + std::hash::Hash,
+    {
+        self.meta_delete_multi_command(keys, meta_flags).await
+    }
+
+    async fn meta_increment<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        delta: Option<u64>,
+        meta_flags: Option<&[&str]>,
+    ) -> Result<Option<MetaValue>, Error> {
+        self.meta_arithmetic_command(
+            key.as_ref(),
+            false,
+            is_quiet,
+            opaque,
+            delta,
+            MetaFlagsArg::Strings(meta_flags),
+        )
+        .await
+    }
+
+    async fn meta_increment_with<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        delta: Option<u64>,
+        flags: &MetaArithmeticFlags,
+    ) -> Result<Option<MetaValue>, Error> {
+        self.meta_arithmetic_command(
+            key.as_ref(),
+            false,
+            is_quiet,
+            opaque,
+            delta,
+            MetaFlagsArg::Encoded(flags.encode()),
+        )
+        .await
+    }
+
+    async fn meta_decrement<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        delta: Option<u64>,
+        meta_flags: Option<&[&str]>,
+    ) -> Result<Option<MetaValue>, Error> {
+        self.meta_arithmetic_command(
+            key.as_ref(),
+            true,
+            is_quiet,
+            opaque,
+            delta,
+            MetaFlagsArg::Strings(meta_flags),
+        )
+        .await
+    }
+
+    async fn meta_decrement_with<K: AsRef<[u8]>>(
+        &mut self,
+        key: K,
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        delta: Option<u64>,
+        flags: &MetaArithmeticFlags,
+    ) -> Result<Option<MetaValue>, Error> {
+        self.meta_arithmetic_command(
+            key.as_ref(),
+            true,
+            is_quiet,
+            opaque,
+            delta,
+            MetaFlagsArg::Encoded(flags.encode()),
+        )
+        .await
+    }
+
+    async fn meta_noop(&mut self) -> Result<(), Error> {
+        self.begin_request().await?;
+
+        self.conn.write_all(b"mn\r\n").await?;
+        self.conn.flush().await?;
+
+        let response = self.drive_receive(parse_meta_get_response).await?;
+        self.end_request();
+
+        match response {
+            MetaResponse::Status(Status::NoOp) => Ok(()),
+            MetaResponse::Status(s) => Err(s.into()),
+            _ => Err(Status::Error(ErrorKind::Protocol(None)).into()),
+        }
+    }
+
+    async fn meta_debug<K: AsRef<[u8]>>(&mut self, key: K) -> Result<Option<MetaDebugInfo>, Error> {
+        let kr = self.prepare_key(key.as_ref(), false)?;
+
+        self.begin_request().await?;
+
+        self.conn.write_all(b"me ").await?;
+        self.conn.write_all(&kr).await?;
+        self.conn.write_all(b"\r\n").await?;
+        self.conn.flush().await?;
+
+        let response = self.drive_receive(parse_meta_debug_response).await?;
+        self.end_request();
+
+        Ok(response)
+    }
+}
+
+// Maps the status of a meta set that did not store its value to its outcome.
+fn meta_set_failure(status: Status) -> Result<MetaSetOutcome, Error> {
+    match status {
+        Status::NotStored => Ok(MetaSetOutcome::NotStored),
+        Status::Exists => Ok(MetaSetOutcome::Exists),
+        Status::NotFound => Ok(MetaSetOutcome::NotFound),
+        s => Err(s.into()),
+    }
+}
+
+// Reports any key returned by the server under the caller's key, which may have been hashed or
+// encoded on its way to the server.
+fn restore_key(response: &mut MetaResponse, key: &[u8]) {
+    if let MetaResponse::Data(Some(items)) = response {
+        for item in items.iter_mut().filter(|item| item.key.is_some()) {
+            item.key = Some(key.to_vec());
+        }
+    }
+}
+
+// Meta flags for a single-key command, either in their string form or encoded from one of the
+// typed builders.
+enum MetaFlagsArg<'a> {
+    Strings(Option<&'a [&'a str]>),
+    Encoded(EncodedMetaFlags),
+}
+
+impl MetaFlagsArg<'_> {
+    fn is_binary_key(&self) -> bool {
+        match self {
+            MetaFlagsArg::Strings(meta_flags) => has_binary_key_flag(*meta_flags),
+            MetaFlagsArg::Encoded(encoded) => encoded.is_binary_key(),
+        }
+    }
+}
+
+impl Client {
+    async fn meta_get_command(
+        &mut self,
+        key: &[u8],
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        flags: MetaFlagsArg<'_>,
+    ) -> Result<Option<MetaValue>, Error> {
+        let kr = self.prepare_key(key, flags.is_binary_key())?;
+
+        if let Some(opaque) = &opaque {
+            Self::validate_opaque_length(opaque)?;
+        }
+
+        self.begin_request().await?;
+
+        self.conn.write_all(b"mg ").await?;
+        self.conn.write_all(&kr).await?;
+
+        Self::check_and_write_opaque(self, opaque).await?;
+
+        self.write_meta_flags_arg(flags, opaque).await?;
+
+        Self::check_and_write_quiet_mode(self, is_quiet).await?;
+
+        self.conn.flush().await?;
+
+        let mut response = self.drive_receive(parse_meta_get_response).await?;
+        restore_key(&mut response, key);
+        self.read_quiet_noop(is_quiet, &response, parse_meta_get_response)
+            .await?;
+        self.end_request();
+
+        retrieved_meta_value(response)
+    }
+
+    async fn meta_set_command(
+        &mut self,
+        key: &[u8],
+        vr: &[u8],
+        is_quiet: bool,
+        opaque: Option<&[u8]>,
+        flags: MetaFlagsArg<'_>,
+    ) -> Result<MetaResponse, Error> {
+        let kr = self.prepare_key(key, flags.is_binary_key())?;
+        self.validate_value_size(vr)?;
 
         if let Some(opaque) = &opaque {
             Self::validate_opaque_length(opaque)?;
         }
 
-        let vr = value.as_bytes();
+        self.begin_request().await?;
 
         self.conn.write_all(b"ms ").await?;
-        self.conn.write_all(kr).await?;
+        self.conn.write_all(&kr).await?;
 
         let vlen = vr.len().to_string();
         self.conn.write_all(b" ").await?;
@@ -216,14 +825,14 @@ impl MetaProtocol for Client {
 
         Self::check_and_write_opaque(self, opaque).await?;
 
-        Self::check_and_write_meta_flags(self, meta_flags, opaque).await?;
+        self.write_meta_flags_arg(flags, opaque).await?;
 
         if is_quiet {
             self.conn.write_all(b" q").await?;
         }
 
         self.conn.write_all(b"\r\n").await?;
-        self.conn.write_all(vr.as_ref()).await?;
+        self.conn.write_all(vr).await?;
         self.conn.write_all(b"\r\n").await?;
 
         if is_quiet {
@@ -232,44 +841,48 @@ impl MetaProtocol for Client {
 
         self.conn.flush().await?;
 
-        match self.drive_receive(parse_meta_set_response).await? {
-            MetaResponse::Status(Status::Stored) => Ok(None),
-            MetaResponse::Status(Status::NoOp) => Ok(None),
-            MetaResponse::Status(s) => Err(s.into()),
-            MetaResponse::Data(d) => d
-                .map(|mut items| {
-                    let item = items.remove(0);
-                    Ok(item)
-                })
-                .transpose(),
-        }
+        let mut response = self.drive_receive(parse_meta_set_response).await?;
+        restore_key(&mut response, key);
+        self.read_quiet_noop(is_quiet, &response, parse_meta_set_response)
+            .await?;
+        self.end_request();
+
+        Ok(response)
     }
 
-    async fn meta_delete<K: AsRef<[u8]>>(
+    async fn meta_delete_command(
         &mut self,
-        key: K,
+        key: &[u8],
         is_quiet: bool,
         opaque: Option<&[u8]>,
-        meta_flags: Option<&[&str]>,
+        flags: MetaFlagsArg<'_>,
     ) -> Result<Option<MetaValue>, Error> {
-        let kr = Self::validate_key_length(key.as_ref())?;
+        let kr = self.prepare_key(key, flags.is_binary_key())?;
 
         if let Some(opaque) = &opaque {
             Self::validate_opaque_length(opaque)?;
         }
 
+        self.begin_request().await?;
+
         self.conn.write_all(b"md ").await?;
-        self.conn.write_all(kr).await?;
+        self.conn.write_all(&kr).await?;
 
         Self::check_and_write_opaque(self, opaque).await?;
 
-        Self::check_and_write_meta_flags(self, meta_flags, opaque).await?;
+        self.write_meta_flags_arg(flags, opaque).await?;
 
         Self::check_and_write_quiet_mode(self, is_quiet).await?;
 
         self.conn.flush().await?;
 
-        match self.drive_receive(parse_meta_delete_response).await? {
+        let mut response = self.drive_receive(parse_meta_delete_response).await?;
+        restore_key(&mut response, key);
+        self.read_quiet_noop(is_quiet, &response, parse_meta_delete_response)
+            .await?;
+        self.end_request();
+
+        match response {
             MetaResponse::Status(Status::Deleted) => Ok(None),
             MetaResponse::Status(Status::Exists) => Err(Error::Protocol(Status::Exists)),
             MetaResponse::Status(Status::NoOp) => Ok(None),
@@ -283,26 +896,33 @@ impl MetaProtocol for Client {
         }
     }
 
-    async fn meta_increment<K: AsRef<[u8]>>(
+    async fn meta_arithmetic_command(
         &mut self,
-        key: K,
+        key: &[u8],
+        decrement: bool,
         is_quiet: bool,
         opaque: Option<&[u8]>,
         delta: Option<u64>,
-        meta_flags: Option<&[&str]>,
+        flags: MetaFlagsArg<'_>,
     ) -> Result<Option<MetaValue>, Error> {
-        let kr = Self::validate_key_length(key.as_ref())?;
+        let kr = self.prepare_key(key, flags.is_binary_key())?;
 
         if let Some(opaque) = &opaque {
             Self::validate_opaque_length(opaque)?;
         }
 
+        self.begin_request().await?;
+
         self.conn.write_all(b"ma ").await?;
-        self.conn.write_all(kr).await?;
+        self.conn.write_all(&kr).await?;
+
+        // skip writing "MI" because it's default behaviour and we can save the bytes.
+        if decrement {
+            self.conn.write_all(b" MD").await?;
+        }
 
         Self::check_and_write_opaque(self, opaque).await?;
 
-        // skip writing "MI" because it's default behaviour and we can save the bytes.
         if let Some(delta) = delta {
             if delta != 1 {
                 self.conn.write_all(b" D").await?;
@@ -310,28 +930,37 @@ impl MetaProtocol for Client {
             }
         }
 
-        if let Some(meta_flags) = meta_flags {
-            for flag in meta_flags {
-                // ignore M flag because it's specific to the method called, ignore q and require param to be used
-                // prefer explicit D and O params over meta flags
-                if flag.starts_with('M')
-                    || flag.starts_with('q')
-                    || (flag.starts_with('D') && delta.is_some())
-                    || (flag.starts_with('O') && opaque.is_some())
-                {
-                    continue;
-                } else {
-                    self.conn.write_all(b" ").await?;
-                    self.conn.write_all(flag.as_bytes()).await?;
+        match flags {
+            MetaFlagsArg::Strings(meta_flags) => {
+                for flag in meta_flags.unwrap_or_default() {
+                    // ignore M flag because it's specific to the method called, ignore q and require param to be used
+                    // prefer explicit D and O params over meta flags
+                    if flag.starts_with('M')
+                        || flag.starts_with('q')
+                        || (flag.starts_with('D') && delta.is_some())
+                        || (flag.starts_with('O') && opaque.is_some())
+                    {
+                        continue;
+                    } else {
+                        self.conn.write_all(b" ").await?;
+                        self.conn.write_all(flag.as_bytes()).await?;
+                    }
                 }
             }
+            MetaFlagsArg::Encoded(encoded) => self.conn.write_all(encoded.as_bytes()).await?,
         }
 
         Self::check_and_write_quiet_mode(self, is_quiet).await?;
 
         self.conn.flush().await?;
 
-        match self.drive_receive(parse_meta_arithmetic_response).await? {
+        let mut response = self.drive_receive(parse_meta_arithmetic_response).await?;
+        restore_key(&mut response, key);
+        self.read_quiet_noop(is_quiet, &response, parse_meta_arithmetic_response)
+            .await?;
+        self.end_request();
+
+        match response {
             MetaResponse::Status(Status::Stored) => Ok(None),
             MetaResponse::Status(Status::NoOp) => Ok(None),
             MetaResponse::Status(s) => Err(s.into()),
@@ -344,64 +973,39 @@ impl MetaProtocol for Client {
         }
     }
 
-    async fn meta_decrement<K: AsRef<[u8]>>(
+    // In quiet mode, only failures and requested data are returned ahead of the no-op, which must
+    // also be read to leave the connection ready for the next command.
+    async fn read_quiet_noop<F>(
         &mut self,
-        key: K,
         is_quiet: bool,
-        opaque: Option<&[u8]>,
-        delta: Option<u64>,
-        meta_flags: Option<&[&str]>,
-    ) -> Result<Option<MetaValue>, Error> {
-        let kr = Self::validate_key_length(key.as_ref())?;
-
-        if let Some(opaque) = &opaque {
-            Self::validate_opaque_length(opaque)?;
-        }
-
-        self.conn.write_all(b"ma ").await?;
-        self.conn.write_all(kr).await?;
-        self.conn.write_all(b" MD").await?;
-
-        Self::check_and_write_opaque(self, opaque).await?;
-
-        if let Some(delta) = delta {
-            if delta != 1 {
-                self.conn.write_all(b" D").await?;
-                self.conn.write_all(delta.to_string().as_bytes()).await?;
+        response: &MetaResponse,
+        op: F,
+    ) -> Result<(), Error>
+    where
+        F: Fn(&[u8]) -> Result<Option<(usize, MetaResponse)>, ErrorKind>,
+    {
+        if is_quiet && *response != MetaResponse::Status(Status::NoOp) {
+            let noop = self.drive_receive(op).await?;
+            if noop != MetaResponse::Status(Status::NoOp) {
+                return Err(Status::Error(ErrorKind::Protocol(None)).into());
             }
         }
+        Ok(())
+    }
 
-        if let Some(meta_flags) = meta_flags {
-            for flag in meta_flags {
-                // ignore M flag because it's specific to the method called, ignore q and require param to be used
-                // prefer explicit D and O params over meta flags
-                if flag.starts_with('M')
-                    || flag.starts_with('q')
-                    || (flag.starts_with('D') && delta.is_some())
-                    || (flag.starts_with('O') && opaque.is_some())
-                {
-                    continue;
-                } else {
-                    self.conn.write_all(b" ").await?;
-                    self.conn.write_all(flag.as_bytes()).await?;
-                }
+    async fn write_meta_flags_arg(
+        &mut self,
+        flags: MetaFlagsArg<'_>,
+        opaque: Option<&[u8]>,
+    ) -> Result<(), Error> {
+        match flags {
+            MetaFlagsArg::Strings(meta_flags) => {
+                Self::check_and_write_meta_flags(self, meta_flags, opaque).await
+            }
+            MetaFlagsArg::Encoded(encoded) => {
+                self.conn.write_all(encoded.as_bytes()).await?;
+                Ok(())
             }
-        }
-
-        Self::check_and_write_quiet_mode(self, is_quiet).await?;
-
-        self.conn.flush().await?;
-
-        match self.drive_receive(parse_meta_arithmetic_response).await? {
-            MetaResponse::Status(Status::Stored) => Ok(None),
-            MetaResponse::Status(Status::NoOp) => Ok(None),
-            MetaResponse::Status(s) => Err(s.into()),
-            MetaResponse::Data(d) => d
-                .map(|mut items| {
-                    let item = items.remove(0);
-                    Ok(item)
-                })
-                .transpose(),
         }
     }
 }
@@ -1,5 +1,10 @@
 mod ascii_protocol;
 pub use ascii_protocol::AsciiProtocol;
 
+mod meta_flags;
+pub use meta_flags::{
+    MetaArithmeticFlags, MetaDeleteFlags, MetaGetFlags, MetaSetFlags, MetaSetMode,
+};
+
 mod meta_protocol;
-pub use meta_protocol::MetaProtocol;
+pub use meta_protocol::{MetaProtocol, MetaSetItem};
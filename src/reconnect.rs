@@ -0,0 +1,93 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// Policy for automatically reconnecting a poisoned [`Client`](crate::Client).
+///
+/// Reconnection is attempted lazily, at the start of the next operation.  After each failed
+/// attempt, further attempts are held off for an exponentially increasing backoff, during which
+/// operations fail immediately with [`Error::Connect`](crate::Error::Connect).
+#[derive(Clone, Debug)]
+pub struct ReconnectPolicy {
+    /// Backoff after the first failed reconnection attempt.
+    pub initial_backoff: Duration,
+    /// Upper bound for the backoff between attempts.
+    pub max_backoff: Duration,
+    /// Fraction of each backoff, between `0.0` and `1.0`, that is randomized to spread out
+    /// reconnection attempts from many clients.
+    pub jitter: f64,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        ReconnectPolicy {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            jitter: 0.5,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Returns the backoff that follows the given number of consecutive failed attempts.
+    pub(crate) fn backoff(&self, failed_attempts: u32) -> Duration {
+        let exponent = failed_attempts.saturating_sub(1).min(31);
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(1 << exponent)
+            .min(self.max_backoff);
+
+        let jitter = self.jitter.clamp(0.0, 1.0) * random_fraction();
+        backoff.mul_f64(1.0 - jitter)
+    }
+}
+
+// Returns a random number in `[0, 1)`, using the randomly seeded keys from the standard library's
+// hasher rather than pulling in a dedicated random number generator.
+fn random_fraction() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReconnectPolicy;
+    use std::time::Duration;
+
+    fn policy(jitter: f64) -> ReconnectPolicy {
+        ReconnectPolicy {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+            jitter,
+        }
+    }
+
+    #[test]
+    fn test_backoff_grows_exponentially() {
+        let policy = policy(0.0);
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(4), Duration::from_millis(800));
+    }
+
+    #[test]
+    fn test_backoff_is_capped() {
+        let policy = policy(0.0);
+
+        assert_eq!(policy.backoff(5), Duration::from_secs(1));
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_backoff_with_jitter_stays_within_bounds() {
+        let policy = policy(0.5);
+
+        for _ in 0..100 {
+            let backoff = policy.backoff(2);
+            assert!(backoff > Duration::from_millis(100));
+            assert!(backoff <= Duration::from_millis(200));
+        }
+    }
+}
@@ -0,0 +1,327 @@
+use std::convert::TryFrom;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use tokio::net::TcpStream;
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::rustls::crypto::ring;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+use tokio_rustls::TlsConnector;
+
+use crate::Error;
+
+/// TLS settings for connections made with a `tls://` or `tcp+tls://` DSN.
+///
+/// Available with the `tls` feature.
+#[derive(Clone, Debug, Default)]
+pub struct TlsConfig {
+    /// PEM file containing the CA certificates used to verify the server's certificate.
+    ///
+    /// The Mozilla root certificates bundled with `webpki-roots` are used if this is `None`.
+    pub ca_certificates: Option<PathBuf>,
+    /// PEM file containing the certificate chain presented to the server for mutual TLS.
+    ///
+    /// Must be set together with `client_key`.
+    pub client_certificate: Option<PathBuf>,
+    /// PEM file containing the private key for `client_certificate`.
+    pub client_key: Option<PathBuf>,
+    /// Name sent to the server for SNI, and that its certificate is verified against.
+    ///
+    /// Defaults to the host in the DSN.
+    pub server_name: Option<String>,
+}
+
+/// Performs a TLS handshake over an established TCP connection to `host`.
+pub(crate) async fn connect(
+    stream: TcpStream,
+    host: &str,
+    config: &TlsConfig,
+) -> Result<TlsStream<TcpStream>, Error> {
+    let connector = TlsConnector::from(Arc::new(client_config(config)?));
+
+    let server_name = config.server_name.as_deref().unwrap_or(host).to_string();
+    let server_name = ServerName::try_from(server_name)
+        .map_err(|e| invalid_input(format!("invalid TLS server name: {}", e)))?;
+
+    connector
+        .connect(server_name, stream)
+        .await
+        .map_err(Error::Connect)
+}
+
+fn client_config(config: &TlsConfig) -> Result<ClientConfig, Error> {
+    let mut roots = RootCertStore::empty();
+    match &config.ca_certificates {
+        Some(path) => {
+            for cert in load_certificates(path)? {
+                roots
+                    .add(cert)
+                    .map_err(|e| invalid_input(format!("invalid CA certificate: {}", e)))?;
+            }
+        }
+        None => roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()),
+    }
+
+    let builder = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| invalid_input(e.to_string()))?
+        .with_root_certificates(roots);
+
+    match (&config.client_certificate, &config.client_key) {
+        (Some(cert), Some(key)) => {
+            let key = PrivateKeyDer::from_pem_file(key)
+                .map_err(|e| invalid_input(format!("failed to load client key: {}", e)))?;
+
+            builder
+                .with_client_auth_cert(load_certificates(cert)?, key)
+                .map_err(|e| invalid_input(format!("invalid client certificate: {}", e)))
+        }
+        (None, None) => Ok(builder.with_no_client_auth()),
+        _ => Err(invalid_input(
+            "client_certificate and client_key must be set together".to_string(),
+        )),
+    }
+}
+
+fn load_certificates(path: &PathBuf) -> Result<Vec<CertificateDer<'static>>, Error> {
+    CertificateDer::pem_file_iter(path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| {
+            invalid_input(format!(
+                "failed to load certificates from {}: {}",
+                path.display(),
+                e
+            ))
+        })
+}
+
+fn invalid_input(message: String) -> Error {
+    Error::Connect(io::Error::new(io::ErrorKind::InvalidInput, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TlsConfig;
+    use crate::{AsciiProtocol, Client, ClientConfig, Error};
+
+    use rcgen::{
+        BasicConstraints, Certificate, CertificateParams, ExtendedKeyUsagePurpose, IsCa, KeyPair,
+    };
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+    use tokio::sync::oneshot;
+    use tokio_rustls::rustls::crypto::ring;
+    use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+    use tokio_rustls::rustls::server::WebPkiClientVerifier;
+    use tokio_rustls::rustls::{RootCertStore, ServerConfig};
+    use tokio_rustls::TlsAcceptor;
+
+    struct Pki {
+        ca: Certificate,
+        ca_key: KeyPair,
+    }
+
+    impl Pki {
+        fn new() -> Self {
+            let ca_key = KeyPair::generate().unwrap();
+            let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+            params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+            let ca = params.self_signed(&ca_key).unwrap();
+
+            Pki { ca, ca_key }
+        }
+
+        fn issue(&self, name: &str, usage: ExtendedKeyUsagePurpose) -> (Certificate, KeyPair) {
+            let key = KeyPair::generate().unwrap();
+            let mut params = CertificateParams::new(vec![name.to_string()]).unwrap();
+            params.extended_key_usages = vec![usage];
+            let cert = params.signed_by(&key, &self.ca, &self.ca_key).unwrap();
+
+            (cert, key)
+        }
+    }
+
+    fn write_pem(name: &str, pem: String) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "async-memcached-{}-{}.pem",
+            std::process::id(),
+            name
+        ));
+        std::fs::write(&path, pem).unwrap();
+        path
+    }
+
+    // Accepts a single TLS connection, reports the SNI sent by the client, and answers every
+    // command with a cache miss.
+    async fn tls_server(
+        pki: &Pki,
+        require_client_auth: bool,
+    ) -> (u16, oneshot::Receiver<Option<String>>) {
+        let (cert, key) = pki.issue("localhost", ExtendedKeyUsagePurpose::ServerAuth);
+        let provider = Arc::new(ring::default_provider());
+
+        let builder = ServerConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .unwrap();
+        let builder = if require_client_auth {
+            let mut roots = RootCertStore::empty();
+            roots.add(pki.ca.der().clone()).unwrap();
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+                .build()
+                .unwrap();
+            builder.with_client_cert_verifier(verifier)
+        } else {
+            builder.with_no_client_auth()
+        };
+
+        let config = builder
+            .with_single_cert(
+                vec![CertificateDer::from(cert.der().to_vec())],
+                PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(key.serialize_der())),
+            )
+            .unwrap();
+
+        let acceptor = TlsAcceptor::from(Arc::new(config));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (sni_tx, sni_rx) = oneshot::channel();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let stream = match acceptor.accept(stream).await {
+                Ok(stream) => stream,
+                Err(_) => return,
+            };
+            let _ = sni_tx.send(stream.get_ref().1.server_name().map(str::to_string));
+
+            let mut stream = BufReader::new(stream);
+            loop {
+                let mut line = String::new();
+                if stream.read_line(&mut line).await.unwrap_or(0) == 0 {
+                    return;
+                }
+                stream.get_mut().write_all(b"END\r\n").await.unwrap();
+            }
+        });
+
+        (port, sni_rx)
+    }
+
+    fn config(tls: TlsConfig) -> ClientConfig {
+        ClientConfig {
+            tls,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connects_with_custom_ca() {
+        let pki = Pki::new();
+        let (port, sni) = tls_server(&pki, false).await;
+
+        let tls = TlsConfig {
+            ca_certificates: Some(write_pem("custom-ca", pki.ca.pem())),
+            ..Default::default()
+        };
+        let mut client = Client::with_config(format!("tls://localhost:{}", port), config(tls))
+            .await
+            .unwrap();
+
+        assert_eq!(client.get("foo").await, Ok(None));
+        assert_eq!(sni.await.unwrap(), Some("localhost".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_untrusted_server() {
+        let pki = Pki::new();
+        let (port, _) = tls_server(&pki, false).await;
+
+        let tls = TlsConfig {
+            ca_certificates: Some(write_pem("untrusted-ca", Pki::new().ca.pem())),
+            ..Default::default()
+        };
+        let result =
+            Client::with_config(format!("tcp+tls://localhost:{}", port), config(tls)).await;
+
+        assert!(matches!(result, Err(Error::Connect(_))));
+    }
+
+    #[tokio::test]
+    async fn test_server_name_overrides_dsn_host() {
+        let pki = Pki::new();
+        let (port, sni) = tls_server(&pki, false).await;
+
+        let tls = TlsConfig {
+            ca_certificates: Some(write_pem("sni-ca", pki.ca.pem())),
+            server_name: Some("localhost".to_string()),
+            ..Default::default()
+        };
+        let mut client = Client::with_config(format!("tls://127.0.0.1:{}", port), config(tls))
+            .await
+            .unwrap();
+
+        assert_eq!(client.get("foo").await, Ok(None));
+        assert_eq!(sni.await.unwrap(), Some("localhost".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_mutual_tls() {
+        let pki = Pki::new();
+        let (port, _) = tls_server(&pki, true).await;
+        let (cert, key) = pki.issue("client", ExtendedKeyUsagePurpose::ClientAuth);
+
+        let tls = TlsConfig {
+            ca_certificates: Some(write_pem("mtls-ca", pki.ca.pem())),
+            client_certificate: Some(write_pem("mtls-cert", cert.pem())),
+            client_key: Some(write_pem("mtls-key", key.serialize_pem())),
+            ..Default::default()
+        };
+        let mut client = Client::with_config(format!("tls://localhost:{}", port), config(tls))
+            .await
+            .unwrap();
+
+        assert_eq!(client.get("foo").await, Ok(None));
+    }
+
+    #[tokio::test]
+    async fn test_mutual_tls_without_client_certificate_fails() {
+        let pki = Pki::new();
+        let (port, _) = tls_server(&pki, true).await;
+
+        let tls = TlsConfig {
+            ca_certificates: Some(write_pem("no-client-cert-ca", pki.ca.pem())),
+            ..Default::default()
+        };
+
+        // With TLS 1.3, the server only rejects the missing certificate after the client considers
+        // the handshake complete, so the failure may surface on the first operation instead.
+        let result =
+            match Client::with_config(format!("tls://localhost:{}", port), config(tls)).await {
+                Ok(mut client) => client.get("foo").await.map(|_| ()),
+                Err(e) => Err(e),
+            };
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_client_certificate_requires_key() {
+        let pki = Pki::new();
+        let (cert, _) = pki.issue("client", ExtendedKeyUsagePurpose::ClientAuth);
+
+        let tls = TlsConfig {
+            client_certificate: Some(write_pem("cert-without-key", cert.pem())),
+            ..Default::default()
+        };
+        let result = Client::with_config("tls://localhost:11211", config(tls)).await;
+
+        assert!(
+            matches!(result, Err(Error::Connect(e)) if e.kind() == std::io::ErrorKind::InvalidInput)
+        );
+    }
+}
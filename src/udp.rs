@@ -0,0 +1,414 @@
+use std::borrow::Cow;
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use fxhash::FxHashMap;
+use tokio::net::{lookup_host, UdpSocket};
+
+use crate::connection::Addr;
+use crate::parser::{parse_ascii_response, ErrorKind, Response, Status};
+use crate::{
+    prepare_key, retrieved_value, retrieved_values, ClientConfig, Error, KeyPolicy, Value,
+};
+
+// Every datagram, in either direction, starts with a frame header made up of four big-endian 16-bit
+// fields: the request ID, the sequence number, the total number of datagrams in the message, and
+// a reserved field that must be zero.
+const HEADER_LEN: usize = 8;
+
+// Requests must fit in a single datagram, which is bounded by the maximum UDP payload over IPv4.
+const MAX_DATAGRAM_LEN: usize = 65_507;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Client for reading from a memcached server over UDP.
+///
+/// Available with the `udp` feature.  UDP avoids the overhead of TCP for small, read-heavy
+/// workloads, at the cost of responses being lost in transit: an operation whose response has not
+/// been received in full within the timeout fails with [`Error::Timeout`].  The server must have
+/// UDP enabled, e.g. by starting it with `-U 11211`.
+///
+/// Each request carries its own ID, so datagrams belonging to a request that timed out or was
+/// cancelled are discarded, and the client remains usable.
+#[derive(Debug)]
+pub struct UdpClient {
+    socket: UdpSocket,
+    timeout: Duration,
+    request_id: u16,
+    recv_buf: Vec<u8>,
+    key_policy: Option<KeyPolicy>,
+}
+
+impl UdpClient {
+    /// Creates a new [`UdpClient`] based on the given data source string.
+    ///
+    /// The DSN should be in the format of `udp://<host>:<port>` or `<IP>:<port>`.
+    pub async fn new<S: AsRef<str>>(dsn: S) -> Result<UdpClient, Error> {
+        Self::with_config(dsn, ClientConfig::default()).await
+    }
+
+    /// Creates a new [`UdpClient`] based on the given data source string and configuration.
+    ///
+    /// Only [`ClientConfig::request_timeout`] and [`ClientConfig::key_policy`] apply.  The request
+    /// timeout bounds how long to wait for all of the datagrams of a response, and defaults to 500
+    /// milliseconds.
+    pub async fn with_config<S: AsRef<str>>(
+        dsn: S,
+        config: ClientConfig,
+    ) -> Result<UdpClient, Error> {
+        let addr = match Addr::parse(dsn.as_ref())? {
+            Addr::Udp(addr) | Addr::Unknown(addr) => addr,
+            _ => {
+                return Err(Error::Connect(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "DSN must use the `udp` scheme",
+                )))
+            }
+        };
+
+        let addr = lookup_host(addr)
+            .await
+            .map_err(Error::Connect)?
+            .next()
+            .ok_or_else(|| {
+                Error::Connect(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "could not resolve to any address",
+                ))
+            })?;
+
+        let local_addr: SocketAddr = match addr {
+            SocketAddr::V4(_) => ([0, 0, 0, 0], 0).into(),
+            SocketAddr::V6(_) => ([0u16; 8], 0).into(),
+        };
+        let socket = UdpSocket::bind(local_addr).await.map_err(Error::Connect)?;
+        socket.connect(addr).await.map_err(Error::Connect)?;
+
+        Ok(UdpClient {
+            socket,
+            timeout: config.request_timeout.unwrap_or(DEFAULT_TIMEOUT),
+            request_id: 0,
+            recv_buf: vec![0; u16::MAX as usize],
+            key_policy: config.key_policy,
+        })
+    }
+
+    /// Gets the given key.
+    ///
+    /// If the key is found, `Some(Value)` is returned, describing the metadata and data of the key.
+    ///
+    /// Otherwise, `None` is returned.
+    pub async fn get<K: AsRef<[u8]>>(&mut self, key: K) -> Result<Option<Value>, Error> {
+        let kr = prepare_key(key.as_ref(), false, self.key_policy.as_ref())?;
+
+        let response = self.retrieve(std::iter::once(kr.as_ref())).await?;
+
+        // The value is returned under the key that was sent, which may have been hashed.
+        let mut value = retrieved_value(response)?;
+        if let (Cow::Owned(_), Some(value)) = (kr, &mut value) {
+            value.key = key.as_ref().to_vec();
+        }
+
+        Ok(value)
+    }
+
+    /// Gets multiple keys.
+    ///
+    /// If any of the keys are found, a vector of [`Value`] will be returned.  Keys that exceed the
+    /// maximum key length, or that contain whitespace or control characters, are skipped unless
+    /// the client has a key policy.
+    ///
+    /// Otherwise, [`Error`] is returned.
+    pub async fn get_multi<I, K>(&mut self, keys: I) -> Result<Vec<Value>, Error>
+    where
+        I: IntoIterator<Item = K>,
+        K: AsRef<[u8]>,
+    {
+        // Invalid keys are skipped, and values are returned under the key that was sent, so hashed
+        // keys are mapped back to the caller's keys.
+        let mut sent_keys = Vec::new();
+        let mut hashed_keys = FxHashMap::default();
+        for key in keys {
+            match prepare_key(key.as_ref(), false, self.key_policy.as_ref()) {
+                Ok(Cow::Owned(kr)) => {
+                    sent_keys.push(kr.clone());
+                    hashed_keys.insert(kr, key.as_ref().to_vec());
+                }
+                Ok(Cow::Borrowed(kr)) => sent_keys.push(kr.to_vec()),
+                Err(_) => continue,
+            }
+        }
+
+        // A request without any keys would be rejected by the server.
+        if sent_keys.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let response = self.retrieve(sent_keys.iter().map(Vec::as_slice)).await?;
+
+        let mut values = retrieved_values(response)?;
+        for value in &mut values {
+            if let Some(key) = hashed_keys.get(&value.key) {
+                value.key = key.clone();
+            }
+        }
+
+        Ok(values)
+    }
+
+    async fn retrieve<'a, I>(&mut self, keys: I) -> Result<Response, Error>
+    where
+        I: Iterator<Item = &'a [u8]>,
+    {
+        self.request_id = self.request_id.wrapping_add(1);
+        let request_id = self.request_id;
+
+        let mut request = Vec::with_capacity(64);
+        request.extend_from_slice(&request_id.to_be_bytes());
+        request.extend_from_slice(&[0, 0, 0, 1, 0, 0]);
+        request.extend_from_slice(b"get");
+        for key in keys {
+            request.push(b' ');
+            request.extend_from_slice(key);
+        }
+        request.extend_from_slice(b"\r\n");
+
+        if request.len() > MAX_DATAGRAM_LEN {
+            return Err(Status::Error(ErrorKind::Client(
+                "Request does not fit in a single datagram".to_string(),
+            ))
+            .into());
+        }
+
+        self.socket.send(&request).await?;
+
+        let payload = tokio::time::timeout(self.timeout, self.receive(request_id))
+            .await
+            .map_err(|_| Error::Timeout)??;
+
+        match parse_ascii_response(&payload) {
+            Ok(Some((n, response))) if n == payload.len() => Ok(response),
+            Ok(_) => Err(Status::Error(ErrorKind::Protocol(None)).into()),
+            Err(kind) => Err(Status::Error(kind).into()),
+        }
+    }
+
+    // Receives the datagrams making up the response to the given request, and returns their
+    // payloads joined in sequence order.  Datagrams may arrive out of order, and datagrams for any
+    // other request are discarded.
+    async fn receive(&mut self, request_id: u16) -> Result<Vec<u8>, Error> {
+        let mut payloads: Vec<Option<Vec<u8>>> = Vec::new();
+        let mut remaining = 0;
+
+        loop {
+            let n = self.socket.recv(&mut self.recv_buf).await?;
+            let Some(header) = FrameHeader::parse(&self.recv_buf[..n]) else {
+                continue;
+            };
+
+            if header.request_id != request_id {
+                continue;
+            }
+
+            if payloads.is_empty() {
+                payloads.resize(header.total as usize, None);
+                remaining = header.total;
+            } else if payloads.len() != header.total as usize {
+                return Err(Status::Error(ErrorKind::Protocol(None)).into());
+            }
+
+            let slot = &mut payloads[header.sequence as usize];
+            if slot.is_none() {
+                *slot = Some(self.recv_buf[HEADER_LEN..n].to_vec());
+                remaining -= 1;
+            }
+
+            if remaining == 0 {
+                return Ok(payloads.into_iter().flatten().flatten().collect());
+            }
+        }
+    }
+}
+
+struct FrameHeader {
+    request_id: u16,
+    sequence: u16,
+    total: u16,
+}
+
+impl FrameHeader {
+    fn parse(datagram: &[u8]) -> Option<Self> {
+        if datagram.len() < HEADER_LEN {
+            return None;
+        }
+
+        let field = |i: usize| u16::from_be_bytes([datagram[i], datagram[i + 1]]);
+        let header = FrameHeader {
+            request_id: field(0),
+            sequence: field(2),
+            total: field(4),
+        };
+
+        if header.total == 0 || header.sequence >= header.total {
+            return None;
+        }
+
+        Some(header)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UdpClient;
+    use crate::{ClientConfig, Error, ErrorKind, KeyPolicy, Status};
+
+    use std::time::Duration;
+    use tokio::net::UdpSocket;
+
+    // Answers each request with the given datagrams, as `(sequence, total, payload)`, framed with
+    // the request's ID.  A request ID offset other than zero makes the server answer with the ID of
+    // a different request.
+    async fn scripted_server(
+        responses: Vec<Vec<(u16, u16, &'static [u8])>>,
+        request_id_offset: u16,
+    ) -> String {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let mut buf = vec![0; 1500];
+            for datagrams in responses {
+                let (n, peer) = socket.recv_from(&mut buf).await.unwrap();
+                assert!(n > 8);
+                assert_eq!(&buf[2..8], &[0, 0, 0, 1, 0, 0]);
+                let request_id = u16::from_be_bytes([buf[0], buf[1]]) + request_id_offset;
+
+                for (sequence, total, payload) in datagrams {
+                    let mut datagram = Vec::new();
+                    datagram.extend_from_slice(&request_id.to_be_bytes());
+                    datagram.extend_from_slice(&sequence.to_be_bytes());
+                    datagram.extend_from_slice(&total.to_be_bytes());
+                    datagram.extend_from_slice(&[0, 0]);
+                    datagram.extend_from_slice(payload);
+                    socket.send_to(&datagram, peer).await.unwrap();
+                }
+            }
+        });
+
+        format!("udp://{}", addr)
+    }
+
+    fn config() -> ClientConfig {
+        ClientConfig {
+            request_timeout: Some(Duration::from_millis(100)),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get() {
+        let dsn = scripted_server(
+            vec![
+                vec![(0, 1, b"VALUE foo 0 3\r\nbar\r\nEND\r\n")],
+                vec![(0, 1, b"END\r\n")],
+            ],
+            0,
+        )
+        .await;
+        let mut client = UdpClient::with_config(dsn, config()).await.unwrap();
+
+        let value = client.get("foo").await.unwrap().unwrap();
+        assert_eq!(value.key, b"foo");
+        assert_eq!(value.data, Some(b"bar".to_vec()));
+
+        assert_eq!(client.get("missing").await, Ok(None));
+    }
+
+    #[tokio::test]
+    async fn test_get_multi_reassembles_out_of_order_datagrams() {
+        let dsn = scripted_server(
+            vec![vec![
+                (2, 3, b"4\r\nEND\r\n"),
+                (0, 3, b"VALUE foo 0 3\r\nbar\r\nVALUE baz 0 4\r\n"),
+                (1, 3, b"qux"),
+                // A duplicate of a datagram that has already been received is ignored.
+                (0, 3, b"garbage"),
+            ]],
+            0,
+        )
+        .await;
+        let mut client = UdpClient::with_config(dsn, config()).await.unwrap();
+
+        let values = client.get_multi(&["foo", "baz"]).await.unwrap();
+
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[0].data, Some(b"bar".to_vec()));
+        assert_eq!(values[1].data, Some(b"qux4".to_vec()));
+    }
+
+    #[tokio::test]
+    async fn test_key_policy_applies_to_lookups() {
+        const HASHED_VALUE: &[u8] = b"VALUE sha256:9835fa6bf4e20a9b9ea812506302e98982721a6cf8d2cae67af57129bf21ae90 0 3\r\nbar\r\nEND\r\n";
+        let dsn = scripted_server(
+            vec![vec![(0, 1, HASHED_VALUE)], vec![(0, 1, HASHED_VALUE)]],
+            0,
+        )
+        .await;
+        let key = "a".repeat(300);
+
+        let mut client = UdpClient::with_config(&dsn, config()).await.unwrap();
+        assert_eq!(
+            client.get(&key).await,
+            Err(Error::Protocol(Status::Error(ErrorKind::KeyTooLong)))
+        );
+
+        let config = ClientConfig {
+            key_policy: Some(KeyPolicy::default()),
+            ..config()
+        };
+        let mut client = UdpClient::with_config(&dsn, config).await.unwrap();
+
+        let value = client.get(&key).await.unwrap().unwrap();
+        assert_eq!(value.key, key.as_bytes());
+
+        let values = client.get_multi([&key]).await.unwrap();
+        assert_eq!(values[0].key, key.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_lost_datagram_times_out() {
+        let dsn = scripted_server(
+            vec![
+                vec![(0, 2, b"VALUE foo 0 3\r\n")],
+                vec![(0, 1, b"VALUE foo 0 3\r\nbar\r\nEND\r\n")],
+            ],
+            0,
+        )
+        .await;
+        let mut client = UdpClient::with_config(dsn, config()).await.unwrap();
+
+        assert_eq!(client.get("foo").await, Err(Error::Timeout));
+
+        // The client remains usable after a lost response.
+        let value = client.get("foo").await.unwrap().unwrap();
+        assert_eq!(value.data, Some(b"bar".to_vec()));
+    }
+
+    #[tokio::test]
+    async fn test_datagrams_for_other_requests_are_ignored() {
+        let dsn = scripted_server(vec![vec![(0, 1, b"END\r\n")]], 1).await;
+        let mut client = UdpClient::with_config(dsn, config()).await.unwrap();
+
+        assert_eq!(client.get("foo").await, Err(Error::Timeout));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_non_udp_dsn() {
+        let result = UdpClient::new("tcp://127.0.0.1:11211").await;
+
+        assert!(
+            matches!(result, Err(Error::Connect(e)) if e.kind() == std::io::ErrorKind::InvalidInput)
+        );
+    }
+}
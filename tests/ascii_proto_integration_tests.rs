@@ -1,4 +1,6 @@
-use async_memcached::{AsciiProtocol, Client, Error, ErrorKind, Status};
+use async_memcached::{
+    AsciiProtocol, CasOutcome, Client, ClientConfig, Error, ErrorKind, KeyPolicy, Namespace, Status,
+};
 use rand::seq::IteratorRandom;
 use serial_test::{parallel, serial};
 
@@ -27,6 +29,25 @@ async fn setup_client(keys: &[&str]) -> Client {
     client
 }
 
+async fn setup_client_with_key_policy(keys: &[&str]) -> Client {
+    let config = ClientConfig {
+        key_policy: Some(KeyPolicy::default()),
+        ..Default::default()
+    };
+    let mut client = Client::with_config("tcp://127.0.0.1:11211", config)
+        .await
+        .expect("Failed to connect to server");
+
+    for key in keys {
+        client
+            .delete_no_reply(key)
+            .await
+            .expect("Failed to delete key");
+    }
+
+    client
+}
+
 #[ignore = "Relies on a running memcached server"]
 #[tokio::test]
 #[parallel]
@@ -495,6 +516,53 @@ async fn test_get_multi_skips_key_too_long() {
     }
 }
 
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_keys_with_invalid_characters_are_rejected() {
+    let key = "invalid-key-test-key";
+
+    let mut client = setup_client(&[key]).await;
+
+    // Without validation, the newline would end the command early and send `delete` on its own.
+    let injected_key = format!("{}\r\ndelete {}", key, key);
+
+    client.set(key, "value", None, None).await.unwrap();
+    assert_eq!(
+        client.set(&injected_key, "value", None, None).await,
+        Err(Error::Protocol(Status::Error(ErrorKind::InvalidKey)))
+    );
+    assert_eq!(
+        client.get("invalid key").await,
+        Err(Error::Protocol(Status::Error(ErrorKind::InvalidKey)))
+    );
+
+    let values = client
+        .get_multi(&[key, injected_key.as_str()])
+        .await
+        .unwrap();
+    assert_eq!(values.len(), 1);
+    assert_eq!(values[0].key, key.as_bytes());
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_add_multi_reports_invalid_keys() {
+    let keys = ["amik-key-1", "amik key 2"];
+
+    let mut client = setup_client(&keys[..1]).await;
+
+    let kv = [(keys[0], "value"), (keys[1], "value")];
+    let results = client.add_multi(&kv, None, None).await.unwrap();
+
+    assert_eq!(results[&keys[0]], Ok(()));
+    assert_eq!(
+        results[&keys[1]],
+        Err(Error::Protocol(Status::Error(ErrorKind::InvalidKey)))
+    );
+}
+
 #[ignore = "Relies on a running memcached server"]
 #[tokio::test]
 #[parallel]
@@ -824,6 +892,473 @@ async fn test_set_multi_with_large_string_values() {
     assert!(get_result.is_ok());
 }
 
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_replace_existing_key() {
+    let key = "replace-existing-key";
+
+    let mut client = setup_client(&[key]).await;
+
+    client
+        .set(key, "original-value", None, None)
+        .await
+        .expect("failed to set");
+
+    let replace_result = client.replace(key, "new-value", None, None).await;
+
+    assert_eq!(replace_result, Ok(()));
+
+    let get_result = client.get(key).await;
+
+    assert_eq!(
+        get_result.unwrap().unwrap().data.unwrap(),
+        b"new-value".to_vec()
+    );
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_replace_nonexistent_key() {
+    let key = "replace-nonexistent-key";
+
+    let mut client = setup_client(&[key]).await;
+
+    let replace_result = client.replace(key, "value", None, None).await;
+
+    assert_eq!(replace_result, Err(Error::Protocol(Status::NotStored)));
+
+    let get_result = client.get(key).await;
+
+    assert!(matches!(get_result, Ok(None)));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_replace_fails_with_key_too_long() {
+    let key = "r".repeat(MAX_KEY_LENGTH + 1);
+
+    let mut client = setup_client(&[&key]).await;
+
+    let replace_result = client.replace(&key, "value", None, None).await;
+
+    assert!(matches!(
+        replace_result,
+        Err(Error::Protocol(Status::Error(ErrorKind::KeyTooLong)))
+    ));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_replace_multi_with_a_key_that_does_not_exist() {
+    let preset_key_0 = "rm-key-already-set-1";
+    let unset_key_1 = "rm-key-not-set";
+    let preset_key_2 = "rm-key-already-set-2";
+    let keys = vec![preset_key_0, unset_key_1, preset_key_2];
+    let values = vec!["new-value-1", "new-value-2", "new-value-3"];
+    let kv: Vec<(&str, &str)> = keys.clone().into_iter().zip(values.into_iter()).collect();
+
+    let mut client = setup_client(&keys).await;
+
+    for key in [preset_key_0, preset_key_2] {
+        client
+            .set(key, "original-value", None, None)
+            .await
+            .expect("failed to set");
+    }
+
+    let results = client
+        .replace_multi(&kv, None, None)
+        .await
+        .expect("expected Ok(HashMap<_>)");
+
+    assert_eq!(results.len(), keys.len());
+    assert!(results[&preset_key_0].is_ok());
+    assert!(matches!(
+        results[&unset_key_1],
+        Err(Error::Protocol(Status::NotStored))
+    ));
+    assert!(results[&preset_key_2].is_ok());
+
+    let values = client.get_multi(&keys).await.unwrap();
+
+    assert_eq!(values.len(), 2);
+    assert!(values
+        .iter()
+        .all(|value| value.data.as_ref().unwrap().starts_with(b"new-value")));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_append_and_prepend_existing_key() {
+    let key = "append-prepend-existing-key";
+
+    let mut client = setup_client(&[key]).await;
+
+    client
+        .set(key, "middle", None, Some(42))
+        .await
+        .expect("failed to set");
+
+    assert_eq!(client.append(key, "-end").await, Ok(()));
+    assert_eq!(client.prepend(key, "start-").await, Ok(()));
+
+    let value = client.get(key).await.unwrap().unwrap();
+
+    assert_eq!(value.data.unwrap(), b"start-middle-end".to_vec());
+    // flags are not modified by append or prepend
+    assert_eq!(value.flags, Some(42));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_append_and_prepend_nonexistent_key() {
+    let key = "append-prepend-nonexistent-key";
+
+    let mut client = setup_client(&[key]).await;
+
+    let append_result = client.append(key, "value").await;
+    assert_eq!(append_result, Err(Error::Protocol(Status::NotStored)));
+
+    let prepend_result = client.prepend(key, "value").await;
+    assert_eq!(prepend_result, Err(Error::Protocol(Status::NotStored)));
+
+    let get_result = client.get(key).await;
+    assert!(matches!(get_result, Ok(None)));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_append_multi_and_prepend_multi() {
+    let preset_key = "apm-key-already-set";
+    let unset_key = "apm-key-not-set";
+    let keys = vec![preset_key, unset_key];
+
+    let mut client = setup_client(&keys).await;
+
+    client
+        .set(preset_key, "-", None, None)
+        .await
+        .expect("failed to set");
+
+    let append_kv = vec![(preset_key, "end"), (unset_key, "end")];
+    let append_results = client.append_multi(&append_kv).await.unwrap();
+
+    assert!(append_results[&preset_key].is_ok());
+    assert!(matches!(
+        append_results[&unset_key],
+        Err(Error::Protocol(Status::NotStored))
+    ));
+
+    let prepend_kv = vec![(preset_key, "start"), (unset_key, "start")];
+    let prepend_results = client.prepend_multi(&prepend_kv).await.unwrap();
+
+    assert!(prepend_results[&preset_key].is_ok());
+    assert!(matches!(
+        prepend_results[&unset_key],
+        Err(Error::Protocol(Status::NotStored))
+    ));
+
+    let value = client.get(preset_key).await.unwrap().unwrap();
+    assert_eq!(value.data.unwrap(), b"start-end".to_vec());
+
+    let get_result = client.get(unset_key).await;
+    assert!(matches!(get_result, Ok(None)));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_gets_returns_cas_identifier() {
+    let key = "gets-key-with-cas";
+
+    let mut client = setup_client(&[key]).await;
+
+    client
+        .set(key, "value", None, None)
+        .await
+        .expect("failed to set");
+
+    let value = client.gets(key).await.unwrap().unwrap();
+
+    assert!(value.cas.is_some());
+    assert_eq!(value.data.unwrap(), b"value".to_vec());
+
+    // a plain get does not request the CAS identifier
+    let value = client.get(key).await.unwrap().unwrap();
+    assert!(value.cas.is_none());
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_gets_with_nonexistent_key() {
+    let key = "gets-nonexistent-key";
+
+    let mut client = setup_client(&[key]).await;
+
+    let gets_result = client.gets(key).await;
+
+    assert!(matches!(gets_result, Ok(None)), "key should not be found");
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_gets_multi() {
+    let keys = ["gm-cas-key1", "gm-cas-key2", "gm-cas-key3"];
+
+    let mut client = setup_client(&keys).await;
+
+    for key in &keys[..2] {
+        client
+            .set(key, "value", None, None)
+            .await
+            .expect("failed to set");
+    }
+
+    let values = client.gets_multi(&keys).await.unwrap();
+
+    assert_eq!(values.len(), 2);
+    assert!(values.iter().all(|value| value.cas.is_some()));
+    assert_ne!(values[0].cas, values[1].cas);
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_cas_stores_value_with_matching_cas() {
+    let key = "cas-key-matching";
+
+    let mut client = setup_client(&[key]).await;
+
+    client
+        .set(key, "original-value", None, None)
+        .await
+        .expect("failed to set");
+
+    let cas_unique = client.gets(key).await.unwrap().unwrap().cas.unwrap();
+
+    let cas_result = client.cas(key, "new-value", cas_unique, None, None).await;
+
+    assert_eq!(cas_result, Ok(CasOutcome::Stored));
+
+    let value = client.gets(key).await.unwrap().unwrap();
+
+    assert_eq!(value.data.unwrap(), b"new-value".to_vec());
+    assert_ne!(value.cas, Some(cas_unique));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_cas_returns_exists_with_stale_cas() {
+    let key = "cas-key-stale";
+
+    let mut client = setup_client(&[key]).await;
+
+    client
+        .set(key, "original-value", None, None)
+        .await
+        .expect("failed to set");
+
+    let cas_unique = client.gets(key).await.unwrap().unwrap().cas.unwrap();
+
+    client
+        .set(key, "concurrent-value", None, None)
+        .await
+        .expect("failed to set");
+
+    let cas_result = client.cas(key, "new-value", cas_unique, None, None).await;
+
+    assert_eq!(cas_result, Ok(CasOutcome::Exists));
+
+    let value = client.get(key).await.unwrap().unwrap();
+
+    assert_eq!(value.data.unwrap(), b"concurrent-value".to_vec());
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_cas_returns_not_found_with_nonexistent_key() {
+    let key = "cas-key-nonexistent";
+
+    let mut client = setup_client(&[key]).await;
+
+    let cas_result = client.cas(key, "value", 1, None, None).await;
+
+    assert_eq!(cas_result, Ok(CasOutcome::NotFound));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_cas_fails_with_key_too_long() {
+    let key = "s".repeat(MAX_KEY_LENGTH + 1);
+
+    let mut client = setup_client(&[&key]).await;
+
+    let cas_result = client.cas(&key, "value", 1, None, None).await;
+
+    assert!(matches!(
+        cas_result,
+        Err(Error::Protocol(Status::Error(ErrorKind::KeyTooLong)))
+    ));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_touch_existing_key() {
+    let key = "touch-existing-key";
+
+    let mut client = setup_client(&[key]).await;
+
+    client
+        .set(key, "value", Some(3600), None)
+        .await
+        .expect("failed to set");
+
+    assert_eq!(client.touch(key, 7200).await, Ok(()));
+
+    let get_result = client.get(key).await;
+    assert_eq!(
+        get_result.unwrap().unwrap().data.unwrap(),
+        b"value".to_vec()
+    );
+
+    // a negative TTL expires the item immediately
+    assert_eq!(client.touch(key, -1).await, Ok(()));
+
+    let get_result = client.get(key).await;
+    assert!(matches!(get_result, Ok(None)));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_touch_nonexistent_key() {
+    let key = "touch-nonexistent-key";
+
+    let mut client = setup_client(&[key]).await;
+
+    let touch_result = client.touch(key, 3600).await;
+
+    assert_eq!(touch_result, Err(Error::Protocol(Status::NotFound)));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_touch_fails_with_key_too_long() {
+    let key = "t".repeat(MAX_KEY_LENGTH + 1);
+
+    let mut client = setup_client(&[&key]).await;
+
+    let touch_result = client.touch(&key, 3600).await;
+
+    assert!(matches!(
+        touch_result,
+        Err(Error::Protocol(Status::Error(ErrorKind::KeyTooLong)))
+    ));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_gat_returns_value_and_updates_ttl() {
+    let key = "gat-existing-key";
+
+    let mut client = setup_client(&[key]).await;
+
+    client
+        .set(key, "value", Some(3600), Some(7))
+        .await
+        .expect("failed to set");
+
+    let value = client.gat(key, -1).await.unwrap().unwrap();
+
+    assert_eq!(value.data.unwrap(), b"value".to_vec());
+    assert_eq!(value.flags, Some(7));
+    assert!(value.cas.is_none());
+
+    // the item was expired by the negative TTL passed to gat
+    let get_result = client.get(key).await;
+    assert!(matches!(get_result, Ok(None)));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_gat_with_nonexistent_key() {
+    let key = "gat-nonexistent-key";
+
+    let mut client = setup_client(&[key]).await;
+
+    let gat_result = client.gat(key, 3600).await;
+
+    assert!(matches!(gat_result, Ok(None)), "key should not be found");
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_gats_returns_cas_identifier() {
+    let key = "gats-existing-key";
+
+    let mut client = setup_client(&[key]).await;
+
+    client
+        .set(key, "value", Some(3600), None)
+        .await
+        .expect("failed to set");
+
+    let cas_unique = client.gets(key).await.unwrap().unwrap().cas;
+    let value = client.gats(key, 7200).await.unwrap().unwrap();
+
+    assert_eq!(value.data.unwrap(), b"value".to_vec());
+    assert!(value.cas.is_some());
+    // touching an item does not modify its CAS identifier
+    assert_eq!(value.cas, cas_unique);
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_gat_multi_and_gats_multi() {
+    let keys = ["gatm-key1", "gatm-key2", "gatm-key3"];
+
+    let mut client = setup_client(&keys).await;
+
+    for key in &keys[..2] {
+        client
+            .set(key, "value", Some(3600), None)
+            .await
+            .expect("failed to set");
+    }
+
+    let values = client.gats_multi(&keys, 7200).await.unwrap();
+
+    assert_eq!(values.len(), 2);
+    assert!(values.iter().all(|value| value.cas.is_some()));
+
+    let values = client.gat_multi(&keys, -1).await.unwrap();
+
+    assert_eq!(values.len(), 2);
+    assert!(values.iter().all(|value| value.cas.is_none()));
+
+    // the items were expired by the negative TTL passed to gat_multi
+    let get_result = client.get_multi(&keys).await;
+    assert!(matches!(get_result, Err(Error::Protocol(Status::NotFound))));
+}
+
 #[ignore = "Relies on a running memcached server"]
 #[tokio::test]
 #[parallel]
@@ -1033,3 +1568,150 @@ async fn test_flush_all() {
     let result = client.get(key).await;
     assert!(matches!(result, Ok(None)));
 }
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_key_policy_hashes_long_and_invalid_keys() {
+    let long_key = format!("kphl-{}", "k".repeat(MAX_KEY_LENGTH));
+    let keys = ["kphl-key", long_key.as_str(), "kphl invalid key"];
+
+    let mut client = setup_client_with_key_policy(&keys).await;
+
+    for key in &keys {
+        client.set(key, *key, None, None).await.unwrap();
+    }
+
+    for key in &keys {
+        let value = client.get(key).await.unwrap().expect("key not found");
+        assert_eq!(value.key, key.as_bytes());
+        assert_eq!(value.data, Some(key.as_bytes().to_vec()));
+    }
+
+    // Values are reported under the caller's keys, not the hashed keys that were sent.
+    let mut values = client.get_multi(&keys).await.unwrap();
+    values.sort_by(|a, b| a.key.cmp(&b.key));
+    let mut expected: Vec<&[u8]> = keys.iter().map(|key| key.as_bytes()).collect();
+    expected.sort();
+    assert_eq!(
+        values.iter().map(|v| v.key.as_slice()).collect::<Vec<_>>(),
+        expected
+    );
+
+    client.delete(&long_key).await.unwrap();
+    assert_eq!(client.get(&long_key).await, Ok(None));
+
+    // Without a policy, the same keys are rejected.
+    let mut client = setup_client(&[]).await;
+    assert_eq!(
+        client.get(&long_key).await,
+        Err(Error::Protocol(Status::Error(ErrorKind::KeyTooLong)))
+    );
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_key_policy_applies_to_set_multi() {
+    let long_key = format!("kpsm-{}", "k".repeat(MAX_KEY_LENGTH));
+    let keys = ["kpsm-key", long_key.as_str()];
+
+    let mut client = setup_client_with_key_policy(&keys).await;
+
+    let kv = [(keys[0], "value"), (keys[1], "value")];
+    let results = client.set_multi(&kv, None, None).await.unwrap();
+    assert!(results.values().all(|result| result.is_ok()));
+
+    let values = client.get_multi(&keys).await.unwrap();
+    assert_eq!(values.len(), 2);
+    assert!(values.iter().any(|value| value.key == long_key.as_bytes()));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_namespace_prefixes_keys() {
+    let keys = ["ns-prefix:a", "ns-prefix:b"];
+
+    let mut client = setup_client(&keys).await;
+    let mut namespace = Namespace::new(&mut client, "ns-prefix:");
+
+    namespace.set("a", "1", None, None).await.unwrap();
+    let results = namespace
+        .set_multi(&[("b", "2")], None, None)
+        .await
+        .unwrap();
+    assert_eq!(results[&"b"], Ok(()));
+
+    let value = namespace.get("a").await.unwrap().expect("key not found");
+    assert_eq!(value.key, b"a".to_vec());
+    assert_eq!(value.data, Some(b"1".to_vec()));
+
+    let mut values = namespace.get_multi(["a", "b"]).await.unwrap();
+    values.sort_by(|a, b| a.key.cmp(&b.key));
+    assert_eq!(
+        values.iter().map(|v| v.key.as_slice()).collect::<Vec<_>>(),
+        vec![b"a".as_slice(), b"b".as_slice()]
+    );
+
+    // The items are stored under the prefixed keys.
+    let value = client.get(keys[1]).await.unwrap().expect("key not found");
+    assert_eq!(value.data, Some(b"2".to_vec()));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_namespace_invalidate_hides_existing_keys() {
+    let prefix = "ns-generation:";
+
+    let mut client = setup_client(&[prefix]).await;
+    let mut namespace = Namespace::with_generation(&mut client, prefix)
+        .await
+        .unwrap();
+    let generation = namespace.generation().expect("no generation");
+
+    namespace.set("a", "1", None, None).await.unwrap();
+    assert!(namespace.get("a").await.unwrap().is_some());
+
+    assert_eq!(namespace.invalidate().await, Ok(generation + 1));
+    assert_eq!(namespace.get("a").await, Ok(None));
+
+    // A new view picks up the current generation.
+    let mut namespace = Namespace::with_generation(&mut client, prefix)
+        .await
+        .unwrap();
+    assert_eq!(namespace.generation(), Some(generation + 1));
+    assert_eq!(namespace.get("a").await, Ok(None));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_values_larger_than_item_size_max_are_rejected() {
+    let keys = ["item-size-max-small", "item-size-max-large"];
+
+    let mut client = setup_client(&keys).await;
+    let item_size_max = client.fetch_item_size_max().await.unwrap();
+
+    let value = "a".repeat(item_size_max + 1);
+    let value_too_large = || Error::Protocol(Status::Error(ErrorKind::ValueTooLarge));
+    assert_eq!(
+        client.set(keys[1], &value, None, None).await,
+        Err(value_too_large())
+    );
+    assert_eq!(
+        client.add(keys[1], &value, None, None).await,
+        Err(value_too_large())
+    );
+
+    // The oversized value is not sent, so the rest of the batch is unaffected.
+    let kv = [(keys[0], "small"), (keys[1], value.as_str())];
+    let results = client.add_multi(&kv, None, None).await.unwrap();
+    assert_eq!(results[&keys[0]], Ok(()));
+    assert_eq!(results[&keys[1]], Err(value_too_large()));
+
+    let values = client.get_multi(&keys).await.unwrap();
+    assert_eq!(values.len(), 1);
+    assert_eq!(values[0].data, Some(b"small".to_vec()));
+}
@@ -0,0 +1,218 @@
+use async_memcached::{AsciiProtocol, Cluster, Error, MetaProtocol, Status};
+use serial_test::parallel;
+
+// NOTE: Each test should run with keys unique to that test to avoid async conflicts.  Because these tests run concurrently,
+// it's possible to delete/overwrite keys created by another test before they're read.
+
+// Both servers point at the same memcached instance, but are identified differently on the continuum,
+// so keys are still spread across two separate connections.
+const SERVERS: [&str; 2] = ["tcp://127.0.0.1:11211", "tcp://localhost:11211"];
+
+async fn setup_cluster(keys: &[&str]) -> Cluster {
+    let mut cluster = Cluster::new(SERVERS)
+        .await
+        .expect("Failed to connect to servers");
+
+    for key in keys {
+        cluster
+            .delete_no_reply(key)
+            .await
+            .expect("Failed to delete key");
+    }
+
+    cluster
+}
+
+fn keys(prefix: &str, count: usize) -> Vec<String> {
+    (0..count).map(|i| format!("{}-{}", prefix, i)).collect()
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_cluster_set_and_get() {
+    let key = "cluster-key-set-and-get";
+    let value = "cluster-value";
+
+    let mut cluster = setup_cluster(&[key]).await;
+
+    cluster
+        .set(key, value, None, None)
+        .await
+        .expect("failed to set");
+
+    let result = cluster.get(key).await.unwrap().unwrap();
+    assert_eq!(result.data.unwrap(), value.as_bytes().to_vec());
+
+    // the value is only stored on the connection that owns the key
+    let result = cluster.client_for_key(key).get(key).await.unwrap().unwrap();
+    assert_eq!(result.data.unwrap(), value.as_bytes().to_vec());
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_cluster_set_multi_and_get_multi_across_servers() {
+    let keys = keys("cluster-key-multi", 20);
+    let key_refs: Vec<&str> = keys.iter().map(|k| k.as_str()).collect();
+
+    let mut cluster = setup_cluster(&key_refs).await;
+
+    let kv: Vec<(&str, &str)> = key_refs.iter().map(|k| (*k, "multi-value")).collect();
+    let results = cluster.set_multi(&kv, None, None).await.unwrap();
+
+    assert_eq!(results.len(), kv.len());
+    assert!(results.values().all(|r| r.is_ok()));
+
+    let mut values = cluster.get_multi(&key_refs).await.unwrap();
+    values.sort_by(|a, b| a.key.cmp(&b.key));
+
+    let mut expected: Vec<Vec<u8>> = keys.iter().map(|k| k.as_bytes().to_vec()).collect();
+    expected.sort();
+
+    assert_eq!(
+        values.into_iter().map(|v| v.key).collect::<Vec<_>>(),
+        expected
+    );
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_cluster_get_multi_with_partial_misses() {
+    let keys = keys("cluster-key-partial-misses", 10);
+    let key_refs: Vec<&str> = keys.iter().map(|k| k.as_str()).collect();
+
+    let mut cluster = setup_cluster(&key_refs).await;
+
+    cluster
+        .set(key_refs[0], "only-value", None, None)
+        .await
+        .unwrap();
+
+    let values = cluster.get_multi(&key_refs).await.unwrap();
+
+    assert_eq!(values.len(), 1);
+    assert_eq!(values[0].key, key_refs[0].as_bytes().to_vec());
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_cluster_get_multi_with_all_misses() {
+    let keys = keys("cluster-key-all-misses", 10);
+    let key_refs: Vec<&str> = keys.iter().map(|k| k.as_str()).collect();
+
+    let mut cluster = setup_cluster(&key_refs).await;
+
+    let result = cluster.get_multi(&key_refs).await;
+
+    assert!(matches!(result, Err(Error::Protocol(Status::NotFound))));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_cluster_delete_multi_no_reply() {
+    let keys = keys("cluster-key-delete-multi", 10);
+    let key_refs: Vec<&str> = keys.iter().map(|k| k.as_str()).collect();
+
+    let mut cluster = setup_cluster(&key_refs).await;
+
+    let kv: Vec<(&str, &str)> = key_refs.iter().map(|k| (*k, "delete-value")).collect();
+    cluster.set_multi(&kv, None, None).await.unwrap();
+
+    cluster.delete_multi_no_reply(&key_refs).await.unwrap();
+
+    for key in &key_refs {
+        assert_eq!(cluster.get(key).await.unwrap(), None);
+    }
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_cluster_meta_set_and_meta_get() {
+    let key = "cluster-meta-key-set-and-get";
+    let value = "cluster-meta-value";
+
+    let mut cluster = setup_cluster(&[key]).await;
+
+    cluster
+        .meta_set(key, value, false, None, None)
+        .await
+        .unwrap();
+
+    let result = cluster
+        .meta_get(key, false, None, Some(&["v"]))
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(result.data.unwrap(), value.as_bytes().to_vec());
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_cluster_meta_get_multi_across_servers() {
+    let keys = keys("cluster-key-meta-get-multi", 20);
+    let key_refs: Vec<&str> = keys.iter().map(|k| k.as_str()).collect();
+
+    let mut cluster = setup_cluster(&key_refs).await;
+
+    for key in &key_refs[..10] {
+        cluster.set(key, *key, None, None).await.unwrap();
+    }
+
+    let values = cluster
+        .meta_get_multi(&key_refs, Some(&["v"]))
+        .await
+        .unwrap();
+
+    assert_eq!(values.len(), 10);
+    for key in &key_refs[..10] {
+        assert_eq!(values[key].data.as_deref(), Some(key.as_bytes()));
+    }
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_cluster_meta_set_and_delete_multi_across_servers() {
+    let keys = keys("cluster-key-meta-set-multi", 20);
+    let key_refs: Vec<&str> = keys.iter().map(|k| k.as_str()).collect();
+
+    let mut cluster = setup_cluster(&key_refs).await;
+
+    let items: Vec<_> = key_refs
+        .iter()
+        .map(|key| (*key, *key, None, None))
+        .collect();
+    let results = cluster.meta_set_multi(&items, None).await.unwrap();
+
+    assert_eq!(results.len(), 20);
+    assert!(results.values().all(|result| result.is_ok()));
+
+    let values = cluster
+        .meta_get_multi(&key_refs, Some(&["v"]))
+        .await
+        .unwrap();
+    assert_eq!(values.len(), 20);
+
+    let results = cluster
+        .meta_delete_multi(&key_refs[..10], None)
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 10);
+    assert!(results.values().all(|result| result.is_ok()));
+
+    let values = cluster
+        .meta_get_multi(&key_refs, Some(&["v"]))
+        .await
+        .unwrap();
+    assert_eq!(values.len(), 10);
+    for key in &key_refs[10..] {
+        assert!(values.contains_key(key));
+    }
+}
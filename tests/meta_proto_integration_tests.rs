@@ -1,4 +1,8 @@
-use async_memcached::{AsciiProtocol, Client, Error, ErrorKind, MetaProtocol, Status};
+use async_memcached::{
+    AsciiProtocol, Client, ClientConfig, Error, ErrorKind, KeyPolicy, MetaArithmeticFlags,
+    MetaDeleteFlags, MetaGetFlags, MetaProtocol, MetaSetFlags, MetaSetItem, MetaSetMode,
+    MetaSetOutcome, Namespace, Status,
+};
 use serial_test::parallel;
 
 // NOTE: Each test should run with keys unique to that test to avoid async conflicts.  Because these tests run concurrently,
@@ -2128,3 +2132,823 @@ async fn test_meta_increment_raises_error_when_opaque_is_too_long() {
         Err(Error::Protocol(Status::Error(ErrorKind::OpaqueTooLong)))
     ));
 }
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_meta_get_multi_with_flags() {
+    let keys = [
+        "meta-get-multi-test-key-1",
+        "meta-get-multi-test-key-2",
+        "meta-get-multi-test-key-missing",
+    ];
+
+    let mut client = setup_client(&keys).await;
+
+    client
+        .set(keys[0], "value-1", Some(300), None)
+        .await
+        .unwrap();
+    client.set(keys[1], "value-2", None, None).await.unwrap();
+
+    let flags = ["v", "t", "k"];
+    let values = client.meta_get_multi(&keys, Some(&flags)).await.unwrap();
+
+    assert_eq!(values.len(), 2);
+
+    let first = &values[&keys[0]];
+    assert_eq!(first.data.as_deref(), Some("value-1".as_bytes()));
+    assert_eq!(first.key.as_deref(), Some(keys[0].as_bytes()));
+    assert!(first.ttl_remaining.unwrap() > 0);
+
+    let second = &values[&keys[1]];
+    assert_eq!(second.data.as_deref(), Some("value-2".as_bytes()));
+    assert_eq!(second.ttl_remaining, Some(-1));
+
+    assert!(!values.contains_key(&keys[2]));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_meta_get_multi_all_missing() {
+    let keys = [
+        "meta-get-multi-test-key-all-missing-1",
+        "meta-get-multi-test-key-all-missing-2",
+    ];
+
+    let mut client = setup_client(&keys).await;
+
+    let values = client.meta_get_multi(&keys, Some(&["v"])).await.unwrap();
+    assert!(values.is_empty());
+
+    // The connection remains usable after the batch.
+    assert_eq!(client.get(keys[0]).await, Ok(None));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_meta_set_multi_with_per_item_ttl_and_flags() {
+    let keys = [
+        "meta-set-multi-test-key-1",
+        "meta-set-multi-test-key-2",
+        "meta-set-multi-test-key-3",
+    ];
+
+    let mut client = setup_client(&keys).await;
+
+    let items = [
+        (keys[0], "value-1", Some(300), Some(7)),
+        (keys[1], "value-2", None, None),
+        (keys[2], "value-3", Some(600), None),
+    ];
+    let results = client.meta_set_multi(&items, None).await.unwrap();
+
+    assert_eq!(results.len(), 3);
+    assert!(results.values().all(|result| result.is_ok()));
+
+    let values = client
+        .meta_get_multi(&keys, Some(&["v", "t", "f"]))
+        .await
+        .unwrap();
+
+    let first = &values[&keys[0]];
+    assert_eq!(first.data.as_deref(), Some("value-1".as_bytes()));
+    assert_eq!(first.flags, Some(7));
+    assert!(first.ttl_remaining.unwrap() > 0);
+
+    let second = &values[&keys[1]];
+    assert_eq!(second.data.as_deref(), Some("value-2".as_bytes()));
+    assert_eq!(second.ttl_remaining, Some(-1));
+
+    assert_eq!(values[&keys[2]].data.as_deref(), Some("value-3".as_bytes()));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_meta_set_multi_surfaces_failures_per_key() {
+    let keys = [
+        "meta-set-multi-test-key-add-existing",
+        "meta-set-multi-test-key-add-new",
+    ];
+    let too_long_key = "a".repeat(MAX_KEY_LENGTH + 1);
+
+    let mut client = setup_client(&keys).await;
+
+    client.set(keys[0], "original", None, None).await.unwrap();
+
+    // Add mode only stores keys that do not exist yet.
+    let items = [
+        (keys[0], "value-1", None, None),
+        (keys[1], "value-2", None, None),
+        (too_long_key.as_str(), "value-3", None, None),
+    ];
+    let results = client.meta_set_multi(&items, Some(&["ME"])).await.unwrap();
+
+    assert_eq!(results[&keys[0]], Err(Error::Protocol(Status::NotStored)));
+    assert_eq!(results[&keys[1]], Ok(()));
+    assert!(matches!(
+        results[&too_long_key.as_str()],
+        Err(Error::Protocol(Status::Error(ErrorKind::KeyTooLong)))
+    ));
+
+    let value = client.get(keys[0]).await.unwrap().unwrap();
+    assert_eq!(value.data, Some("original".as_bytes().to_vec()));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_meta_delete_multi() {
+    let keys = [
+        "meta-delete-multi-test-key-1",
+        "meta-delete-multi-test-key-2",
+        "meta-delete-multi-test-key-3",
+    ];
+
+    let mut client = setup_client(&keys).await;
+
+    for key in &keys {
+        client.set(key, "value", None, None).await.unwrap();
+    }
+
+    let results = client.meta_delete_multi(&keys[..2], None).await.unwrap();
+
+    assert_eq!(results.len(), 2);
+    assert!(results.values().all(|result| result.is_ok()));
+
+    let values = client.meta_get_multi(&keys, Some(&["v"])).await.unwrap();
+    assert_eq!(values.len(), 1);
+    assert!(values.contains_key(&keys[2]));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_meta_delete_multi_with_cas_mismatch() {
+    let keys = [
+        "meta-delete-multi-test-key-cas-1",
+        "meta-delete-multi-test-key-cas-2",
+    ];
+
+    let mut client = setup_client(&keys).await;
+
+    for key in &keys {
+        client.set(key, "value", None, None).await.unwrap();
+    }
+
+    // Neither key has this CAS value, so both deletes fail.
+    let results = client
+        .meta_delete_multi(&keys, Some(&["C1"]))
+        .await
+        .unwrap();
+
+    assert_eq!(results[&keys[0]], Err(Error::Protocol(Status::Exists)));
+    assert_eq!(results[&keys[1]], Err(Error::Protocol(Status::Exists)));
+
+    // The connection remains usable after the batch.
+    let values = client.meta_get_multi(&keys, Some(&["v"])).await.unwrap();
+    assert_eq!(values.len(), 2);
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_meta_set_with_and_get_with_typed_flags() {
+    let key = "meta-typed-flags-test-key-set-get";
+
+    let mut client = setup_client(&[key]).await;
+
+    let set_flags = MetaSetFlags::new().client_flags(42).ttl(300).return_cas();
+    let outcome = client
+        .meta_set_with(key, "value", false, None, &set_flags)
+        .await
+        .unwrap();
+    let Some(stored) = (match outcome {
+        MetaSetOutcome::Stored(stored) => stored,
+        outcome => panic!("Expected the value to be stored, got {:?}", outcome),
+    }) else {
+        panic!("Expected the CAS value to be returned");
+    };
+    assert!(stored.cas.is_some());
+
+    let get_flags = MetaGetFlags::new()
+        .return_value()
+        .return_cas()
+        .return_client_flags()
+        .return_ttl()
+        .return_key();
+    let value = client
+        .meta_get_with(key, false, Some(b"opaque"), &get_flags)
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(value.data.as_deref(), Some("value".as_bytes()));
+    assert_eq!(value.cas, stored.cas);
+    assert_eq!(value.flags, Some(42));
+    assert_eq!(value.key.as_deref(), Some(key.as_bytes()));
+    assert_eq!(value.opaque_token.as_deref(), Some("opaque".as_bytes()));
+    assert!(value.ttl_remaining.unwrap() > 0);
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_meta_get_with_vivify_on_miss() {
+    let key = "meta-typed-flags-test-key-vivify";
+
+    let mut client = setup_client(&[key]).await;
+
+    let flags = MetaGetFlags::new().return_value().vivify_on_miss(30);
+    let value = client
+        .meta_get_with(key, false, None, &flags)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // The first miss creates an empty item and wins the right to recache it.
+    assert_eq!(value.is_recache_winner, Some(true));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_meta_delete_with_invalidate() {
+    let key = "meta-typed-flags-test-key-invalidate";
+
+    let mut client = setup_client(&[key]).await;
+
+    client.set(key, "value", None, None).await.unwrap();
+
+    let flags = MetaDeleteFlags::new().invalidate().update_ttl(30);
+    let result = client.meta_delete_with(key, false, None, &flags).await;
+    assert_eq!(result, Ok(None));
+
+    let value = client
+        .meta_get_with(key, false, None, &MetaGetFlags::new().return_value())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(value.is_stale, Some(true));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_meta_increment_and_decrement_with_typed_flags() {
+    let key = "meta-typed-flags-test-key-arithmetic";
+
+    let mut client = setup_client(&[key]).await;
+
+    let flags = MetaArithmeticFlags::new()
+        .return_value()
+        .vivify_on_miss(60)
+        .initial_value(10);
+
+    let value = client
+        .meta_increment_with(key, false, None, None, &flags)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(value.data.as_deref(), Some("10".as_bytes()));
+
+    let value = client
+        .meta_increment_with(key, false, None, Some(5), &flags)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(value.data.as_deref(), Some("15".as_bytes()));
+
+    let value = client
+        .meta_decrement_with(key, false, None, Some(3), &flags)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(value.data.as_deref(), Some("12".as_bytes()));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_meta_set_with_modes() {
+    let key = "meta-set-with-modes-test-key";
+
+    let mut client = setup_client(&[key]).await;
+
+    let replace = MetaSetFlags::new().mode(MetaSetMode::Replace);
+    let outcome = client.meta_set_with(key, "b", false, None, &replace).await;
+    assert_eq!(outcome, Ok(MetaSetOutcome::NotStored));
+
+    let add = MetaSetFlags::new().mode(MetaSetMode::Add);
+    let outcome = client.meta_set_with(key, "b", false, None, &add).await;
+    assert_eq!(outcome, Ok(MetaSetOutcome::Stored(None)));
+
+    let outcome = client.meta_set_with(key, "x", false, None, &add).await;
+    assert_eq!(outcome, Ok(MetaSetOutcome::NotStored));
+
+    let append = MetaSetFlags::new().mode(MetaSetMode::Append);
+    let outcome = client.meta_set_with(key, "c", false, None, &append).await;
+    assert_eq!(outcome, Ok(MetaSetOutcome::Stored(None)));
+
+    let prepend = MetaSetFlags::new().mode(MetaSetMode::Prepend);
+    let outcome = client.meta_set_with(key, "a", false, None, &prepend).await;
+    assert_eq!(outcome, Ok(MetaSetOutcome::Stored(None)));
+
+    let value = client.get(key).await.unwrap().unwrap();
+    assert_eq!(value.data, Some(b"abc".to_vec()));
+
+    let set = MetaSetFlags::new().mode(MetaSetMode::Set);
+    let outcome = client.meta_set_with(key, "d", false, None, &set).await;
+    assert_eq!(outcome, Ok(MetaSetOutcome::Stored(None)));
+
+    let value = client.get(key).await.unwrap().unwrap();
+    assert_eq!(value.data, Some(b"d".to_vec()));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_meta_set_with_compare_cas() {
+    let key = "meta-set-with-compare-cas-test-key";
+
+    let mut client = setup_client(&[key]).await;
+
+    let outcome = client
+        .meta_set_with(key, "a", false, None, &MetaSetFlags::new().compare_cas(1))
+        .await;
+    assert_eq!(outcome, Ok(MetaSetOutcome::NotFound));
+
+    let outcome = client
+        .meta_set_with(key, "a", false, None, &MetaSetFlags::new().return_cas())
+        .await
+        .unwrap();
+    let cas = match outcome {
+        MetaSetOutcome::Stored(Some(item)) => item.cas.unwrap(),
+        outcome => panic!("Expected the value to be stored, got {:?}", outcome),
+    };
+
+    let outcome = client
+        .meta_set_with(
+            key,
+            "b",
+            false,
+            None,
+            &MetaSetFlags::new().compare_cas(cas + 1),
+        )
+        .await;
+    assert_eq!(outcome, Ok(MetaSetOutcome::Exists));
+
+    let flags = MetaSetFlags::new().compare_cas(cas).explicit_cas(cas + 100);
+    let outcome = client.meta_set_with(key, "b", false, None, &flags).await;
+    assert_eq!(outcome, Ok(MetaSetOutcome::Stored(None)));
+
+    let value = client.gets(key).await.unwrap().unwrap();
+    assert_eq!(value.data, Some(b"b".to_vec()));
+    assert_eq!(value.cas, Some(cas + 100));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_meta_set_with_quiet_mode_reports_failures() {
+    let key = "meta-set-with-quiet-mode-test-key";
+
+    let mut client = setup_client(&[key]).await;
+
+    let add = MetaSetFlags::new().mode(MetaSetMode::Add);
+    let outcome = client.meta_set_with(key, "a", true, None, &add).await;
+    assert_eq!(outcome, Ok(MetaSetOutcome::Stored(None)));
+
+    let outcome = client.meta_set_with(key, "b", true, None, &add).await;
+    assert_eq!(outcome, Ok(MetaSetOutcome::NotStored));
+
+    // The no-op following the failure has been read, so the connection remains usable.
+    let value = client.get(key).await.unwrap().unwrap();
+    assert_eq!(value.data, Some(b"a".to_vec()));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_get_or_recompute_loads_on_miss_only() {
+    let key = "get-or-recompute-test-key-miss";
+
+    let mut client = setup_client(&[key]).await;
+
+    let value = client
+        .get_or_recompute(key, 300, 10, || async { Ok::<_, Error>("loaded") })
+        .await
+        .unwrap();
+    assert_eq!(value, b"loaded");
+
+    let value = client
+        .get_or_recompute(key, 300, 10, || async {
+            panic!("loader should not be called for a fresh value");
+            #[allow(unreachable_code)]
+            Ok::<&str, Error>("")
+        })
+        .await
+        .unwrap();
+    assert_eq!(value, b"loaded");
+
+    let stored = client.get(key).await.unwrap().unwrap();
+    assert_eq!(stored.data, Some(b"loaded".to_vec()));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_get_or_recompute_serves_stale_value_while_recomputing() {
+    let key = "get-or-recompute-test-key-stale";
+
+    let mut client = setup_client(&[key]).await;
+    let mut other = setup_client(&[]).await;
+
+    client.set(key, "old", None, None).await.unwrap();
+    client.meta_invalidate(key).await.unwrap();
+
+    let value = client
+        .get_or_recompute(key, 300, 10, || async {
+            // While this caller recomputes the value, other callers are served the stale one.
+            let stale = other
+                .get_or_recompute(key, 300, 10, || async {
+                    panic!("only the winner should call the loader");
+                    #[allow(unreachable_code)]
+                    Ok::<&str, Error>("")
+                })
+                .await?;
+            assert_eq!(stale, b"old");
+
+            Ok::<_, Error>("new")
+        })
+        .await
+        .unwrap();
+    assert_eq!(value, b"new");
+
+    let stored = client.get(key).await.unwrap().unwrap();
+    assert_eq!(stored.data, Some(b"new".to_vec()));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_get_or_recompute_waits_for_winner_on_miss() {
+    let key = "get-or-recompute-test-key-wait";
+
+    let mut client = setup_client(&[key]).await;
+    let mut other = setup_client(&[]).await;
+
+    let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+    let winner = tokio::spawn(async move {
+        client
+            .get_or_recompute(key, 300, 10, || async {
+                started_tx.send(()).unwrap();
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                Ok::<_, Error>("winner")
+            })
+            .await
+    });
+
+    started_rx.await.unwrap();
+    let value = other
+        .get_or_recompute(key, 300, 10, || async {
+            panic!("only the winner should call the loader");
+            #[allow(unreachable_code)]
+            Ok::<&str, Error>("")
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(value, b"winner");
+    assert_eq!(winner.await.unwrap().unwrap(), b"winner");
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_get_or_recompute_releases_placeholder_when_loader_fails() {
+    let key = "get-or-recompute-test-key-loader-error";
+
+    let mut client = setup_client(&[key]).await;
+
+    let result = client
+        .get_or_recompute(key, 300, 10, || async {
+            Err::<&str, _>(Error::Protocol(Status::NotStored))
+        })
+        .await;
+    assert_eq!(result, Err(Error::Protocol(Status::NotStored)));
+
+    // The next caller wins straight away instead of waiting for the placeholder to expire.
+    let value = client
+        .get_or_recompute(key, 300, 10, || async { Ok::<_, Error>("retried") })
+        .await
+        .unwrap();
+    assert_eq!(value, b"retried");
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_meta_invalidate_missing_key() {
+    let key = "meta-invalidate-test-key-missing";
+
+    let mut client = setup_client(&[key]).await;
+
+    assert_eq!(client.meta_invalidate(key).await, Ok(()));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_meta_noop() {
+    let mut client = setup_client(&[]).await;
+
+    assert_eq!(client.meta_noop().await, Ok(()));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_meta_debug() {
+    let key = "meta-debug-test-key";
+    let value = "test-value";
+
+    let mut client = setup_client(&[key]).await;
+
+    client
+        .set(key, value, None, None)
+        .await
+        .expect("failed to set");
+
+    let info = client
+        .meta_debug(key)
+        .await
+        .expect("failed to meta debug")
+        .expect("key not found");
+
+    assert_eq!(info.key, key.as_bytes());
+    assert_eq!(info.expiration, -1);
+    assert!(!info.fetched);
+    assert!(info.cas > 0);
+    assert!(info.size as usize > value.len());
+
+    // Debugging a key does not count as fetching it.
+    let info = client.meta_debug(key).await.unwrap().unwrap();
+    assert!(!info.fetched);
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_meta_debug_missing_key() {
+    let key = "meta-debug-test-key-missing";
+
+    let mut client = setup_client(&[key]).await;
+
+    assert_eq!(client.meta_debug(key).await, Ok(None));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_meta_binary_key_round_trip() {
+    let key: &[u8] = b"meta-binary-key-test \x00\xff\r\n";
+    let value = "test-value";
+
+    let mut client = setup_client(&[]).await;
+
+    let set_flags = MetaSetFlags::new().binary_key();
+    let outcome = client
+        .meta_set_with(key, value, false, None, &set_flags)
+        .await
+        .expect("failed to meta set");
+    assert!(matches!(outcome, MetaSetOutcome::Stored(_)));
+
+    let get_flags = MetaGetFlags::new().binary_key().return_value().return_key();
+    let result = client
+        .meta_get_with(key, false, None, &get_flags)
+        .await
+        .expect("failed to meta get")
+        .expect("key not found");
+    assert_eq!(result.key.as_deref(), Some(key));
+    assert_eq!(result.data, Some(value.as_bytes().to_vec()));
+
+    // The same key can be used with string meta flags.
+    let result = client
+        .meta_get(key, false, None, Some(&["b", "k", "v"]))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(result.key.as_deref(), Some(key));
+
+    let delete_flags = MetaDeleteFlags::new().binary_key();
+    client
+        .meta_delete_with(key, false, None, &delete_flags)
+        .await
+        .expect("failed to meta delete");
+    assert_eq!(
+        client.meta_get_with(key, false, None, &get_flags).await,
+        Ok(None)
+    );
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_meta_binary_key_that_is_too_long_once_encoded() {
+    // Base64 encoding grows keys by a third, so this key only fits before being encoded.
+    let key = vec![0xffu8; MAX_KEY_LENGTH];
+
+    let mut client = setup_client(&[]).await;
+
+    let result = client.meta_get(&key, false, None, Some(&["b", "v"])).await;
+    assert_eq!(
+        result,
+        Err(Error::Protocol(Status::Error(ErrorKind::KeyTooLong)))
+    );
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_meta_multi_with_binary_keys() {
+    let keys: [&[u8]; 3] = [
+        b"meta-binary-multi-test-key-1 \x00",
+        b"meta-binary-multi-test-key-2 \xff",
+        b"meta-binary-multi-test-key-3 \n",
+    ];
+
+    let mut client = setup_client(&[]).await;
+
+    let items: Vec<MetaSetItem<&[u8], &str>> =
+        keys.iter().map(|key| (*key, "value", None, None)).collect();
+    let results = client.meta_set_multi(&items, Some(&["b"])).await.unwrap();
+    assert!(results.values().all(|result| result.is_ok()));
+
+    let values = client
+        .meta_get_multi(&keys, Some(&["b", "k", "v"]))
+        .await
+        .unwrap();
+    assert_eq!(values.len(), 3);
+    for key in &keys {
+        assert_eq!(values[key].key.as_deref(), Some(*key));
+    }
+
+    let results = client.meta_delete_multi(&keys, Some(&["b"])).await.unwrap();
+    assert!(results.values().all(|result| result.is_ok()));
+
+    let values = client
+        .meta_get_multi(&keys, Some(&["b", "v"]))
+        .await
+        .unwrap();
+    assert!(values.is_empty());
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_meta_multi_reports_invalid_keys() {
+    let keys = ["meta-multi-invalid-key-test-key", "meta multi invalid key"];
+
+    let mut client = setup_client(&keys[..1]).await;
+
+    let items = [
+        (keys[0], "value", None, None),
+        (keys[1], "value", None, None),
+    ];
+    let results = client.meta_set_multi(&items, None).await.unwrap();
+    assert_eq!(results[&keys[0]], Ok(()));
+    assert_eq!(
+        results[&keys[1]],
+        Err(Error::Protocol(Status::Error(ErrorKind::InvalidKey)))
+    );
+
+    let values = client.meta_get_multi(&keys, Some(&["v"])).await.unwrap();
+    assert_eq!(values.len(), 1);
+    assert!(values.contains_key(&keys[0]));
+
+    let results = client.meta_delete_multi(&keys, None).await.unwrap();
+    assert_eq!(results[&keys[0]], Ok(()));
+    assert_eq!(
+        results[&keys[1]],
+        Err(Error::Protocol(Status::Error(ErrorKind::InvalidKey)))
+    );
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_meta_key_policy_hashes_long_keys() {
+    let long_key = format!("meta-key-policy-test-key-{}", "k".repeat(MAX_KEY_LENGTH));
+    let keys = ["meta-key-policy-test-key", long_key.as_str()];
+
+    let config = ClientConfig {
+        key_policy: Some(KeyPolicy::default()),
+        ..Default::default()
+    };
+    let mut client = Client::with_config("tcp://127.0.0.1:11211", config)
+        .await
+        .expect("Failed to connect to server");
+
+    client
+        .meta_set(&long_key, "value", false, None, None)
+        .await
+        .unwrap();
+
+    let value = client
+        .meta_get(&long_key, false, None, Some(&["k", "v"]))
+        .await
+        .unwrap()
+        .expect("key not found");
+    assert_eq!(value.key, Some(long_key.as_bytes().to_vec()));
+    assert_eq!(value.data, Some(b"value".to_vec()));
+
+    let values = client
+        .meta_get_multi(&keys, Some(&["k", "v"]))
+        .await
+        .unwrap();
+    assert_eq!(values.len(), 1);
+    assert_eq!(values[&keys[1]].key.as_deref(), Some(long_key.as_bytes()));
+
+    let results = client.meta_delete_multi(&keys[1..], None).await.unwrap();
+    assert_eq!(results[&keys[1]], Ok(()));
+    assert_eq!(
+        client.meta_get(&long_key, false, None, None).await,
+        Ok(None)
+    );
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_meta_namespace_prefixes_keys() {
+    let keys = ["meta-ns-prefix:a", "meta-ns-prefix:b"];
+
+    let mut client = setup_client(&keys).await;
+    let mut namespace = Namespace::new(&mut client, "meta-ns-prefix:");
+
+    namespace
+        .meta_set("a", "1", false, None, None)
+        .await
+        .unwrap();
+    let items: [MetaSetItem<&str, &str>; 1] = [("b", "2", None, None)];
+    let results = namespace.meta_set_multi(&items, None).await.unwrap();
+    assert_eq!(results[&"b"], Ok(()));
+
+    let value = namespace
+        .meta_get("a", false, None, Some(&["k", "v"]))
+        .await
+        .unwrap()
+        .expect("key not found");
+    assert_eq!(value.key, Some(b"a".to_vec()));
+    assert_eq!(value.data, Some(b"1".to_vec()));
+
+    let values = namespace
+        .meta_get_multi(&["a", "b"], Some(&["k", "v"]))
+        .await
+        .unwrap();
+    assert_eq!(values[&"a"].key, Some(b"a".to_vec()));
+    assert_eq!(values[&"b"].data, Some(b"2".to_vec()));
+
+    let results = namespace
+        .meta_delete_multi(&["a", "b"], None)
+        .await
+        .unwrap();
+    assert_eq!(results[&"a"], Ok(()));
+    assert_eq!(results[&"b"], Ok(()));
+    assert_eq!(client.meta_get(keys[0], false, None, None).await, Ok(None));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_meta_set_rejects_values_larger_than_item_size_max() {
+    let key = "meta-set-item-size-max-test-key";
+
+    let config = ClientConfig {
+        item_size_max: Some(LARGE_PAYLOAD_SIZE),
+        ..Default::default()
+    };
+    let mut client = Client::with_config("tcp://127.0.0.1:11211", config)
+        .await
+        .expect("Failed to connect to server");
+    client.delete_no_reply(key).await.unwrap();
+
+    let value = "a".repeat(LARGE_PAYLOAD_SIZE + 1);
+    assert_eq!(
+        client.meta_set(key, &value, false, None, None).await,
+        Err(Error::Protocol(Status::Error(ErrorKind::ValueTooLarge)))
+    );
+
+    let items: [MetaSetItem<&str, &str>; 1] = [(key, value.as_str(), None, None)];
+    let results = client.meta_set_multi(&items, None).await.unwrap();
+    assert_eq!(
+        results[&key],
+        Err(Error::Protocol(Status::Error(ErrorKind::ValueTooLarge)))
+    );
+
+    assert_eq!(client.meta_get(key, false, None, None).await, Ok(None));
+}
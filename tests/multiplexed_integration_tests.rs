@@ -0,0 +1,135 @@
+use async_memcached::{CoalescingClient, CoalescingConfig, Error, MultiplexedClient, Status};
+use serial_test::parallel;
+
+// NOTE: Each test should run with keys unique to that test to avoid async conflicts.  Because these tests run concurrently,
+// it's possible to delete/overwrite keys created by another test before they're read.
+
+async fn setup_client() -> MultiplexedClient {
+    MultiplexedClient::new("tcp://127.0.0.1:11211")
+        .await
+        .expect("Failed to connect to server")
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_multiplexed_concurrent_set_and_get() {
+    let client = setup_client().await;
+
+    let tasks: Vec<_> = (0..50)
+        .map(|i| {
+            let client = client.clone();
+            tokio::spawn(async move {
+                let key = format!("multiplexed-key-concurrent-{}", i);
+                let value = format!("multiplexed-value-{}", i);
+
+                client
+                    .set(&key, &value, None, None)
+                    .await
+                    .expect("failed to set");
+                let result = client.get(&key).await.unwrap().unwrap();
+                assert_eq!(result.data.unwrap(), value.into_bytes());
+            })
+        })
+        .collect();
+
+    for task in tasks {
+        task.await.unwrap();
+    }
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_multiplexed_get_multi() {
+    let keys = ["multiplexed-key-multi-1", "multiplexed-key-multi-2"];
+    let client = setup_client().await;
+
+    for key in keys {
+        client.set(key, key, None, None).await.unwrap();
+    }
+
+    let values = client.get_multi(keys).await.unwrap();
+    assert_eq!(values.len(), 2);
+    for (value, key) in values.iter().zip(keys) {
+        assert_eq!(value.data.as_deref(), Some(key.as_bytes()));
+    }
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_multiplexed_concurrent_increments() {
+    let key = "multiplexed-key-increment";
+    let client = setup_client().await;
+
+    client.set(key, 0u64, None, None).await.unwrap();
+
+    let tasks: Vec<_> = (0..20)
+        .map(|_| {
+            let client = client.clone();
+            tokio::spawn(async move { client.increment(key, 1).await.unwrap() })
+        })
+        .collect();
+
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    assert_eq!(client.increment(key, 0).await, Ok(20));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_multiplexed_add_replace_touch_and_delete() {
+    let key = "multiplexed-key-add-replace-delete";
+    let client = setup_client().await;
+
+    let _ = client.delete(key).await;
+
+    assert_eq!(
+        client.replace(key, "value", None, None).await,
+        Err(Error::Protocol(Status::NotStored))
+    );
+    client.add(key, "value", None, None).await.unwrap();
+    client.replace(key, "replaced", None, None).await.unwrap();
+    client.touch(key, 60).await.unwrap();
+
+    let result = client.get(key).await.unwrap().unwrap();
+    assert_eq!(result.data.unwrap(), b"replaced");
+
+    client.delete(key).await.unwrap();
+    assert_eq!(client.get(key).await, Ok(None));
+    assert_eq!(
+        client.delete(key).await,
+        Err(Error::Protocol(Status::NotFound))
+    );
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_coalescing_client_get_and_meta_get() {
+    let keys = ["coalescing-key-1", "coalescing-key-2"];
+    let missing_key = "coalescing-key-missing";
+    let client = setup_client().await;
+
+    for key in keys {
+        client.set(key, key, None, None).await.unwrap();
+    }
+    let _ = client.delete(missing_key).await;
+
+    let coalescing = CoalescingClient::new(client, CoalescingConfig::default());
+    let (first, second, missing, meta) = futures::join!(
+        coalescing.get(keys[0]),
+        coalescing.get(keys[1]),
+        coalescing.get(missing_key),
+        coalescing.meta_get(keys[0], Some(&["v"])),
+    );
+
+    assert_eq!(first.unwrap().unwrap().data.unwrap(), keys[0].as_bytes());
+    assert_eq!(second.unwrap().unwrap().data.unwrap(), keys[1].as_bytes());
+    assert_eq!(missing.unwrap(), None);
+    assert_eq!(meta.unwrap().unwrap().data.unwrap(), keys[0].as_bytes());
+}
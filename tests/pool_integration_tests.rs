@@ -0,0 +1,177 @@
+use async_memcached::{
+    AsciiProtocol, Error, HealthCheck, Pool, PoolConfig, PoolStatus, PooledClient,
+};
+use serial_test::parallel;
+use std::time::Duration;
+
+// NOTE: Each test should run with keys unique to that test to avoid async conflicts.  Because these tests run concurrently,
+// it's possible to delete/overwrite keys created by another test before they're read.
+
+async fn setup_pool(config: PoolConfig) -> Pool {
+    Pool::new("tcp://127.0.0.1:11211", config)
+        .await
+        .expect("Failed to create pool")
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_pooled_client_executes_commands() {
+    let key = "pool-key-executes-commands";
+    let value = "pool-value";
+
+    let pool = setup_pool(PoolConfig::default()).await;
+    let mut client = pool.get().await.expect("failed to check out a client");
+
+    client
+        .set(key, value, None, None)
+        .await
+        .expect("failed to set");
+
+    let result = client.get(key).await.unwrap().unwrap();
+
+    assert_eq!(result.data.unwrap(), value.as_bytes().to_vec());
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_pool_opens_min_size_connections() {
+    let config = PoolConfig {
+        min_size: 3,
+        ..Default::default()
+    };
+
+    let pool = setup_pool(config).await;
+
+    assert_eq!(
+        pool.status(),
+        PoolStatus {
+            idle: 3,
+            checked_out: 0
+        }
+    );
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_pool_reuses_returned_connections() {
+    let pool = setup_pool(PoolConfig::default()).await;
+
+    let client = pool.get().await.expect("failed to check out a client");
+    assert_eq!(
+        pool.status(),
+        PoolStatus {
+            idle: 0,
+            checked_out: 1
+        }
+    );
+
+    drop(client);
+    assert_eq!(
+        pool.status(),
+        PoolStatus {
+            idle: 1,
+            checked_out: 0
+        }
+    );
+
+    let _client = pool.get().await.expect("failed to check out a client");
+    assert_eq!(
+        pool.status(),
+        PoolStatus {
+            idle: 0,
+            checked_out: 1
+        }
+    );
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_pool_checkout_times_out_when_exhausted() {
+    let config = PoolConfig {
+        max_size: 1,
+        checkout_timeout: Some(Duration::from_millis(50)),
+        ..Default::default()
+    };
+
+    let pool = setup_pool(config).await;
+
+    let _client = pool.get().await.expect("failed to check out a client");
+    let result = pool.get().await;
+
+    assert!(matches!(result, Err(Error::Timeout)));
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_pool_waits_for_returned_connection() {
+    let config = PoolConfig {
+        max_size: 1,
+        ..Default::default()
+    };
+
+    let pool = setup_pool(config).await;
+    let client = pool.get().await.expect("failed to check out a client");
+
+    let waiter = {
+        let pool = pool.clone();
+        tokio::spawn(async move { pool.get().await.map(|_: PooledClient| ()) })
+    };
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    drop(client);
+
+    assert!(waiter.await.unwrap().is_ok());
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_pool_closes_idle_connections_above_min_size() {
+    let config = PoolConfig {
+        min_size: 1,
+        idle_timeout: Some(Duration::from_millis(10)),
+        ..Default::default()
+    };
+
+    let pool = setup_pool(config).await;
+
+    let first = pool.get().await.expect("failed to check out a client");
+    let second = pool.get().await.expect("failed to check out a client");
+    drop(first);
+    drop(second);
+
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    // one of the expired connections is closed, and the one kept open to satisfy min_size is handed out
+    let _client = pool.get().await.expect("failed to check out a client");
+    assert_eq!(
+        pool.status(),
+        PoolStatus {
+            idle: 0,
+            checked_out: 1
+        }
+    );
+}
+
+#[ignore = "Relies on a running memcached server"]
+#[tokio::test]
+#[parallel]
+async fn test_pool_health_checks() {
+    for health_check in [HealthCheck::Version, HealthCheck::MetaNoOp] {
+        let config = PoolConfig {
+            min_size: 1,
+            health_check,
+            ..Default::default()
+        };
+
+        let pool = setup_pool(config).await;
+        let mut client = pool.get().await.expect("failed to check out a client");
+
+        assert!(client.get("pool-key-health-check").await.is_ok());
+    }
+}
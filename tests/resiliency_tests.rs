@@ -1,4 +1,4 @@
-use async_memcached::{AsciiProtocol, Client};
+use async_memcached::{AsciiProtocol, Client, ClientConfig, ReconnectPolicy};
 
 use toxiproxy_rust::{
     client::Client as ToxiproxyClient,
@@ -8,6 +8,7 @@ use toxiproxy_rust::{
 use std::net::{SocketAddr, ToSocketAddrs};
 use std::ops::Deref;
 use std::sync::{atomic::AtomicUsize, Once, OnceLock};
+use std::time::Duration;
 
 static TOXIPROXY_INIT: Once = Once::new();
 static TOXI_ADDR: OnceLock<SocketAddr> = OnceLock::new();
@@ -322,4 +323,105 @@ mod tests {
             ));
         }
     }
+
+    #[ignore = "Relies on a running memcached server and toxiproxy service"]
+    #[test]
+    fn test_get_times_out_with_toxic_client_via_latency() {
+        let keys = vec!["latency-key1"];
+
+        let (toxic_proxy, toxic_local_addr) = create_proxy_and_config();
+        let toxic_local_url = "tcp://".to_string() + &toxic_local_addr;
+
+        let (rt, _, _) = setup_runtime_and_clients(&toxic_local_url, &keys);
+
+        let config = ClientConfig {
+            read_timeout: Some(Duration::from_millis(100)),
+            ..Default::default()
+        };
+        let mut toxic_client = rt
+            .block_on(Client::with_config(&toxic_local_url, config))
+            .unwrap();
+
+        // Delay every response from the server well beyond the client's read timeout.
+        let _ = toxic_proxy
+            .with_latency("downstream".into(), 1000, 0, 1.0)
+            .apply(|| {
+                rt.block_on(async {
+                    let result = toxic_client.get("latency-key1").await;
+                    assert_eq!(result, Err(async_memcached::Error::Timeout));
+                });
+            });
+    }
+
+    #[ignore = "Relies on a running memcached server and toxiproxy service"]
+    #[test]
+    fn test_set_multi_times_out_with_toxic_client_via_request_timeout() {
+        let keys = vec!["request-timeout-key1", "request-timeout-key2"];
+        let values = vec!["value1", "value2"];
+
+        let (toxic_proxy, toxic_local_addr) = create_proxy_and_config();
+        let toxic_local_url = "tcp://".to_string() + &toxic_local_addr;
+
+        let (rt, _, _) = setup_runtime_and_clients(&toxic_local_url, &keys);
+
+        let config = ClientConfig {
+            request_timeout: Some(Duration::from_millis(100)),
+            ..Default::default()
+        };
+        let mut toxic_client = rt
+            .block_on(Client::with_config(&toxic_local_url, config))
+            .unwrap();
+
+        let kv: Vec<(&str, &str)> = keys.clone().into_iter().zip(values).collect();
+
+        let _ = toxic_proxy
+            .with_latency("downstream".into(), 1000, 0, 1.0)
+            .apply(|| {
+                rt.block_on(async {
+                    let result = toxic_client.set_multi(&kv, None, None).await;
+                    assert_eq!(result, Err(async_memcached::Error::Timeout));
+                });
+            });
+    }
+
+    #[ignore = "Relies on a running memcached server and toxiproxy service"]
+    #[test]
+    fn test_client_reconnects_after_toxic_proxy_comes_back_up() {
+        let keys = vec!["reconnect-key1"];
+
+        let (toxic_proxy, toxic_local_addr) = create_proxy_and_config();
+        let toxic_local_url = "tcp://".to_string() + &toxic_local_addr;
+
+        let (rt, _, _) = setup_runtime_and_clients(&toxic_local_url, &keys);
+
+        let config = ClientConfig {
+            reconnect: Some(ReconnectPolicy {
+                initial_backoff: Duration::ZERO,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let mut toxic_client = rt
+            .block_on(Client::with_config(&toxic_local_url, config))
+            .unwrap();
+
+        let _ = toxic_proxy.with_down(|| {
+            rt.block_on(async {
+                let result = toxic_client
+                    .set("reconnect-key1", "value1", None, None)
+                    .await;
+                assert!(result.is_err());
+            });
+        });
+
+        assert!(toxic_client.is_poisoned());
+
+        let result = rt.block_on(async {
+            toxic_client
+                .set("reconnect-key1", "value1", None, None)
+                .await
+        });
+        assert_eq!(result, Ok(()));
+        assert!(!toxic_client.is_poisoned());
+    }
 }